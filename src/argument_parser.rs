@@ -0,0 +1,168 @@
+use regex::Regex;
+
+use crate::confidence::{HEADER_MATCHED, IN_USAGE};
+use crate::models::{CommandArgument, ComponentType, LineArgument, UsageComponent};
+
+/// Upper-case usage keywords that stand for flags or subcommands, not arguments
+const NON_ARGUMENT_KEYWORDS: [&str; 6] = [
+    "FLAGS",
+    "OPTIONS",
+    "OPTION",
+    "COMMAND",
+    "SUBCOMMAND",
+    "FLAG",
+];
+
+pub fn is_argument_section(section_header: &str) -> bool {
+    let section_header = section_header.to_lowercase();
+    section_header == "args"
+        || (section_header.contains("argument")
+            && !section_header.contains("flag")
+            && !section_header.contains("option"))
+}
+
+/// Strips the `<>`, `[]` and `...` decorations around an argument name
+pub fn argument_name(token: &str) -> String {
+    token
+        .trim_end_matches("...")
+        .trim_matches(['<', '>', '[', ']'])
+        .trim_end_matches("...")
+        .to_string()
+}
+
+/// Upper-case placeholders such as `NAME`, `SOURCE_FILE` or `TYPE/NAME`
+pub fn is_argument_keyword(name: &str) -> bool {
+    name.chars().any(|c| c.is_ascii_uppercase())
+        && name
+            .chars()
+            .all(|c| c.is_ascii_uppercase() || c.is_ascii_digit() || matches!(c, '_' | '-' | '/'))
+        && !NON_ARGUMENT_KEYWORDS.contains(&name)
+}
+
+pub fn parse_argument_line(trimmed_line: &str, section_header_name: &str) -> LineArgument {
+    let column_re = Regex::new(r"\s{2,}|\t").unwrap();
+    let (spec, description) = match column_re.find(trimmed_line) {
+        Some(gap) => (&trimmed_line[..gap.start()], &trimmed_line[gap.end()..]),
+        None => match trimmed_line.split_once(' ') {
+            Some((spec, description)) => (spec, description),
+            None => (trimmed_line, ""),
+        },
+    };
+    let description = description.split_whitespace().collect::<Vec<_>>().join(" ");
+
+    LineArgument {
+        name: argument_name(spec),
+        description: if description.is_empty() {
+            None
+        } else {
+            Some(description)
+        },
+        required: !spec.starts_with('['),
+        variadic: spec.ends_with("..."),
+        parent_header: section_header_name.to_string(),
+    }
+}
+
+/// Walks usage components in order, collecting the positional arguments.
+/// Arguments nested in an optional or alternative group are not required.
+/// Values bound to a flag (`--output <file>`) are part of that flag.
+fn collect_usage_arguments(
+    components: &[UsageComponent],
+    required: bool,
+    variadic: bool,
+    arguments: &mut Vec<CommandArgument>,
+) {
+    for component in components {
+        match component.component_type {
+            ComponentType::Group => collect_usage_arguments(
+                &component.children,
+                required && component.required,
+                variadic || component.repeatable,
+                arguments,
+            ),
+            ComponentType::AlternativeGroup => collect_usage_arguments(
+                &component.alternatives,
+                false,
+                variadic || component.repeatable,
+                arguments,
+            ),
+            ComponentType::Argument | ComponentType::Keyword | ComponentType::KeyValuePair => {
+                let name = argument_name(&component.name);
+                let is_argument = component.component_type != ComponentType::Keyword
+                    || is_argument_keyword(&name);
+                if is_argument && !arguments.iter().any(|a| a.name == name) {
+                    arguments.push(CommandArgument {
+                        position: arguments.len(),
+                        name,
+                        required: required && component.required,
+                        variadic: variadic || component.repeatable,
+                        description: None,
+                        evidence: vec![IN_USAGE.to_string()],
+                    });
+                }
+            }
+            ComponentType::Flag | ComponentType::OptionsShortcut | ComponentType::Separator => {}
+        }
+    }
+}
+
+/// Derives the positional arguments of a command from its usage lines, then
+/// fills in descriptions from the `Arguments:` section. Arguments only listed
+/// in that section are appended after the ones from the usage lines. Each usage
+/// line is an alternative, so an argument is only required if all of them
+/// require it.
+pub fn derive_arguments(
+    usages: &[Vec<UsageComponent>],
+    section_arguments: &[LineArgument],
+) -> Vec<CommandArgument> {
+    let usage_arguments: Vec<Vec<CommandArgument>> = usages
+        .iter()
+        .map(|usage_components| {
+            let mut arguments = Vec::new();
+            collect_usage_arguments(usage_components, true, false, &mut arguments);
+            arguments
+        })
+        .collect();
+
+    let mut arguments: Vec<CommandArgument> = Vec::new();
+    for argument in usage_arguments.iter().flatten() {
+        match arguments.iter_mut().find(|a| a.name == argument.name) {
+            Some(existing) => existing.variadic |= argument.variadic,
+            None => arguments.push(CommandArgument {
+                position: arguments.len(),
+                name: argument.name.clone(),
+                required: usage_arguments.iter().all(|line_arguments| {
+                    line_arguments
+                        .iter()
+                        .any(|a| a.name == argument.name && a.required)
+                }),
+                variadic: argument.variadic,
+                description: None,
+                evidence: argument.evidence.clone(),
+            }),
+        }
+    }
+
+    for section_argument in section_arguments {
+        match arguments
+            .iter_mut()
+            .find(|a| a.name.eq_ignore_ascii_case(&section_argument.name))
+        {
+            Some(argument) => {
+                argument.description = section_argument.description.clone();
+                argument.variadic |= section_argument.variadic;
+                argument.evidence.push(HEADER_MATCHED.to_string());
+            }
+            None => arguments.push(CommandArgument {
+                name: section_argument.name.clone(),
+                position: arguments.len(),
+                required: section_argument.required,
+                variadic: section_argument.variadic,
+                description: section_argument.description.clone(),
+                evidence: vec![HEADER_MATCHED.to_string()],
+            }),
+        }
+    }
+
+    arguments
+}
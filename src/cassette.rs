@@ -0,0 +1,55 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::models::Invocation;
+
+/// One file per invocation, named after its arguments, e.g.
+/// `kubectl_get_--help.json` for `kubectl get --help`. Spaces become `_` and
+/// any other character that isn't safe in a file name, `_` included, is
+/// percent-encoded, so no two invocations share a file.
+fn cassette_file(cassette_dir: &Path, argv: &[String]) -> PathBuf {
+    let mut name = String::new();
+    for c in argv.join(" ").chars() {
+        match c {
+            c if c.is_ascii_alphanumeric() || matches!(c, '-' | '.') => name.push(c),
+            ' ' => name.push('_'),
+            c => {
+                let mut bytes = [0; 4];
+                for byte in c.encode_utf8(&mut bytes).bytes() {
+                    name.push_str(&format!("%{:02X}", byte));
+                }
+            }
+        }
+    }
+    cassette_dir.join(format!("{}.json", name))
+}
+
+pub fn record_invocation(cassette_dir: &Path, invocation: &Invocation) {
+    fs::create_dir_all(cassette_dir).expect("Failed to create cassette directory");
+    let json = serde_json::to_string_pretty(invocation).expect("Failed to serialize invocation");
+    fs::write(cassette_file(cassette_dir, &invocation.argv), json)
+        .expect("Failed to write cassette file");
+}
+
+/// Serves a recorded invocation. Anything missing from the cassette replays as
+/// a failed run, the same way a command the program doesn't know would.
+pub fn replay_invocation(cassette_dir: &Path, argv: &[String]) -> Invocation {
+    let recorded = fs::read_to_string(cassette_file(cassette_dir, argv))
+        .ok()
+        .and_then(|json| serde_json::from_str::<Invocation>(&json).ok())
+        .filter(|invocation| invocation.argv == argv);
+
+    recorded.unwrap_or_else(|| Invocation {
+        argv: argv.to_vec(),
+        env: BTreeMap::new(),
+        working_dir: None,
+        stdout: String::new(),
+        stderr: format!(
+            "No recorded invocation of `{}` in {}",
+            argv.join(" "),
+            cassette_dir.display()
+        ),
+        status: -1,
+    })
+}
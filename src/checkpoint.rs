@@ -0,0 +1,106 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::PathBuf;
+
+use crate::models::Invocation;
+
+/// Every invocation of a crawl, appended to `./out/<prog>/.checkpoint.jsonl` as
+/// soon as it completes, so an interrupted crawl loses at most the invocation
+/// that was running.
+///
+/// Resuming walks the command tree again from the top, serving the invocations
+/// saved so far instead of running them. The tree, visited commands and depths
+/// come out the same as in the interrupted crawl, and only what it didn't get
+/// to is run.
+///
+/// Invocations are told apart by their arguments and environment, so the
+/// variants of a crawl can share a checkpoint.
+#[derive(Clone)]
+pub struct Checkpoint {
+    path: PathBuf,
+    resumed: HashMap<(Vec<String>, BTreeMap<String, String>), Invocation>,
+}
+
+impl Checkpoint {
+    pub fn path_for(program_name: &str) -> PathBuf {
+        PathBuf::from("./out")
+            .join(program_name)
+            .join(".checkpoint.jsonl")
+    }
+
+    /// Starts a new checkpoint, discarding the one of an earlier crawl
+    pub fn start(program_name: &str) -> Self {
+        let path = Checkpoint::path_for(program_name);
+        if path.exists() {
+            println!(
+                "Discarding the checkpoint of an interrupted crawl of {} (use --resume to continue it)",
+                program_name
+            );
+            fs::remove_file(&path).expect("Failed to remove old checkpoint");
+        }
+        Checkpoint {
+            path,
+            resumed: HashMap::new(),
+        }
+    }
+
+    /// Continues from the checkpoint of an interrupted crawl, if there is one
+    pub fn resume(program_name: &str) -> Self {
+        let path = Checkpoint::path_for(program_name);
+        // A line cut short by the interruption is skipped and run again
+        let resumed: HashMap<_, Invocation> = fs::read_to_string(&path)
+            .unwrap_or_default()
+            .lines()
+            .filter_map(|line| serde_json::from_str::<Invocation>(line).ok())
+            .map(|invocation| {
+                (
+                    (invocation.argv.clone(), invocation.env.clone()),
+                    invocation,
+                )
+            })
+            .collect();
+
+        if resumed.is_empty() {
+            println!(
+                "No interrupted crawl of {} to resume, parsing everything",
+                program_name
+            );
+        } else {
+            println!(
+                "Resuming crawl of {} ({} invocations already done)",
+                program_name,
+                resumed.len()
+            );
+        }
+        Checkpoint { path, resumed }
+    }
+
+    pub fn resumed_invocation(
+        &self,
+        argv: &[String],
+        env: &BTreeMap<String, String>,
+    ) -> Option<&Invocation> {
+        self.resumed.get(&(argv.to_vec(), env.clone()))
+    }
+
+    pub fn save(&self, invocation: &Invocation) {
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent).expect("Failed to create checkpoint directory");
+        }
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .expect("Failed to open checkpoint");
+        let line = serde_json::to_string(invocation).expect("Failed to serialize invocation");
+        writeln!(file, "{}", line).expect("Failed to write checkpoint");
+    }
+
+    /// Removes the checkpoint once the crawl has been written out
+    pub fn finish(&self) {
+        if self.path.exists() {
+            fs::remove_file(&self.path).expect("Failed to remove checkpoint");
+        }
+    }
+}
@@ -1,4 +1,8 @@
-use crate::models::FileOutputFormat;
+use crate::models::{
+    CrawlEnvironment, CrawlOptions, CrawlReport, ErrorProbeReport, FileOutputFormat, FlagAnalysis,
+    HelpPathsAudit, LatencyProfile, LintConfig, LintReport, ParseOutput, ReportFormat,
+    ReportOptions, Severity, TerminalAudit, TypoOutcome, TypoProbeReport,
+};
 use cli_parser::extract_cli_structure;
 use dialoguer::{Confirm, Select};
 use keyword_extractor::extract_keywords_from_json;
@@ -13,10 +17,24 @@ use warp::Filter;
 
 use crate::cli_parser;
 use crate::comparison;
+use crate::confidence::{filter_by_confidence, mark_low_confidence};
+use crate::error_probe::probe_errors;
+use crate::exec_prefix::ExecPrefix;
+use crate::flag_analysis::analyze_flags;
+use crate::help_paths::audit_help_paths;
+use crate::incremental::{IncrementalReport, compare_help_pages};
 use crate::keyword_extractor;
+use crate::lint::{
+    LINT_RULES, default_lint_config_path, lint_structure, load_lint_config, unknown_rules,
+};
 use crate::models::OutputFile;
+use crate::overlay::{apply_overlay, default_overlay_path, load_overlay};
 use crate::replicator;
+use crate::report_formats::{change_results, lint_results, write_report};
 use crate::summary_generator::generate_summary;
+use crate::terminal_audit::audit_terminal;
+use crate::typo_probe::probe_typos;
+use crate::variants::{filter_by_variant, merge_variants};
 
 pub fn run_get_template_web_files(force: bool) {
     let home_dir = env::var("HOME")
@@ -225,23 +243,81 @@ fn show_manual_template_download_instructions(target_dir: &Path) {
 
 pub fn run_cli_parser(
     command: &str,
-    output_path: Option<&PathBuf>,
-    format: Option<&String>,
-    tag: Option<&String>,
+    output: &ParseOutput,
+    report_path: Option<&PathBuf>,
+    overlay_path: Option<&PathBuf>,
+    options: &CrawlOptions,
 ) {
     use crate::models::ParseOutputFormat;
+    let ParseOutput {
+        path: output_path,
+        format,
+        tag,
+        min_confidence,
+    } = *output;
+
+    // Loaded before crawling, so a broken overlay doesn't waste a crawl
+    let overlay_file = overlay_path
+        .cloned()
+        .unwrap_or_else(|| default_overlay_path(command));
+    let overlay = if overlay_path.is_some() || overlay_file.exists() {
+        match load_overlay(&overlay_file) {
+            Ok(overlay) => Some((overlay_file, overlay)),
+            Err(e) => {
+                println!("{}", e);
+                return;
+            }
+        }
+    } else {
+        None
+    };
 
     // First try to load existing JSON file, fall back to re-parsing if not found
-    let structure: serde_json::Value = {
+    let mut structure: serde_json::Value = {
         let json_filename = format!("{}.json", command.split('/').next_back().unwrap_or("cli"));
         let json_path = Path::new(&json_filename);
         if json_path.exists() {
             let json_content = fs::read_to_string(json_path).expect("Failed to read JSON file");
             serde_json::from_str(&json_content).expect("Failed to parse JSON file")
+        } else if options.variants.is_empty() {
+            extract_cli_structure(command, None, options)
         } else {
-            extract_cli_structure(command, None)
+            let crawls = options
+                .variants
+                .iter()
+                .map(|variant| {
+                    println!("Crawling variant {}", variant.name);
+                    let structure =
+                        extract_cli_structure(command, None, &options.for_variant(variant));
+                    (variant.clone(), structure)
+                })
+                .collect();
+            merge_variants(crawls)
         }
     };
+    if let Some(previous_structure) = &options.previous_structure {
+        print_incremental_report(&compare_help_pages(previous_structure, &structure));
+    }
+
+    if let Some((overlay_file, overlay)) = &overlay {
+        println!("Applying overlay {}", overlay_file.display());
+        for key in apply_overlay(&mut structure, overlay) {
+            println!(
+                "Warning: overlay entry '{}' targets a command or flag that no longer exists",
+                key
+            );
+        }
+    }
+    // Absent when the structure was loaded from an existing JSON file
+    let crawl_report: Option<CrawlReport> = structure
+        .get("metadata")
+        .and_then(|m| m.get("crawl_report"))
+        .and_then(|r| serde_json::from_value(r.clone()).ok());
+    let latency_profile: Option<LatencyProfile> = structure
+        .get("metadata")
+        .and_then(|m| m.get("latency"))
+        .and_then(|l| serde_json::from_value(l.clone()).ok());
+
     let program_name = structure
         .get("name")
         .expect("Failed to get program name")
@@ -297,7 +373,7 @@ pub fn run_cli_parser(
 
             let filename = match output_format {
                 ParseOutputFormat::TypeScriptDirectory => program_name.to_string(),
-                _ => "parsed".to_string(),
+                _ => format!("parsed.{}", output_format.get_file_extension()),
             };
 
             base_dir.join(filename)
@@ -319,24 +395,601 @@ pub fn run_cli_parser(
             println!("Zod TypeScript schema file saved successfully!");
         }
         ParseOutputFormat::TypeScriptDirectory => {
-            generate_typescript_directory(&structure, &out_path, program_version);
+            let mut exported = structure.clone();
+            if let Some(min_confidence) = min_confidence {
+                filter_by_confidence(&mut exported, min_confidence);
+            }
+            generate_typescript_directory(&exported, &out_path, program_version);
             println!("TypeScript directory structure created successfully!");
         }
     }
 
     println!("Location: {}", out_path.display());
 
+    if let Some(crawl_report) = &crawl_report {
+        print_crawl_report(crawl_report);
+        if let Some(report_path) = report_path {
+            let out_file = OutputFile::new(report_path, FileOutputFormat::Json);
+            out_file.write_json_output_file(json!(crawl_report));
+            println!("Crawl report saved to {}", report_path.display());
+        }
+    } else if report_path.is_some() {
+        println!("No crawl report, the structure was not crawled");
+    }
+    if let Some(latency_profile) = &latency_profile {
+        print_latency_profile(latency_profile);
+    }
+
+    if let Some(checkpoint) = &options.checkpoint {
+        checkpoint.finish();
+    }
+
     if output_path.is_none() {
         println!("Tip: Files are organized by program name and version in ~/.config/clint/parsed/");
     }
 }
 
+/// How many failures and warnings the crawl and probe summaries list before
+/// cutting off
+const CRAWL_REPORT_MAX_LINES: usize = 10;
+
+fn print_crawl_report(report: &CrawlReport) {
+    let duration_ms: u64 = report.nodes.iter().map(|node| node.duration_ms).sum();
+    let failed = report.nodes.iter().filter(|node| node.status != 0).count();
+    let unclassified: usize = report
+        .nodes
+        .iter()
+        .map(|node| node.unclassified_lines.len())
+        .sum();
+    let warnings: usize = report.nodes.iter().map(|node| node.warnings.len()).sum();
+    let reused = report.nodes.iter().filter(|node| node.reused).count();
+    println!(
+        "Crawl report: {} commands in {:.1}s{}, {} failed, {} unclassified lines, {} warnings",
+        report.nodes.len(),
+        duration_ms as f64 / 1000.0,
+        if reused > 0 {
+            format!(" ({} reused)", reused)
+        } else {
+            String::new()
+        },
+        failed,
+        unclassified,
+        warnings
+    );
+
+    let mut lines = Vec::new();
+    for node in &report.nodes {
+        if node.status != 0 {
+            let stderr = node.stderr_excerpt.as_deref().unwrap_or_default();
+            lines.push(format!(
+                "  x {} (exit {}) {}",
+                node.command_path,
+                node.status,
+                stderr.lines().next().unwrap_or_default()
+            ));
+        }
+        for warning in &node.warnings {
+            lines.push(format!("  ! {}: {}", node.command_path, warning));
+        }
+    }
+    for line in lines.iter().take(CRAWL_REPORT_MAX_LINES) {
+        println!("{}", line.trim_end());
+    }
+    if lines.len() > CRAWL_REPORT_MAX_LINES {
+        println!(
+            "  ... and {} more (see --report)",
+            lines.len() - CRAWL_REPORT_MAX_LINES
+        );
+    }
+}
+
+/// Runs every command of a parse with an unknown flag, a missing argument and
+/// an extra argument, and saves what the program printed
+pub fn run_error_probe(
+    input_json: &PathBuf,
+    output_path: Option<&PathBuf>,
+    exec_prefix: Option<ExecPrefix>,
+    argument_probes: bool,
+) {
+    let Some((structure, environment)) = load_probe_target(input_json, exec_prefix) else {
+        return;
+    };
+    if argument_probes {
+        println!(
+            "Warning: --argument-probes runs every command with wrong arguments. A command that accepts them does what it does with them (creating, deleting, ...), so only run it against a sandbox."
+        );
+    }
+    let report = probe_errors(
+        &structure,
+        &CrawlOptions::live(environment),
+        argument_probes,
+    );
+
+    // Next to the parse, so the probes of two versions are easy to diff
+    let output_path = output_path
+        .cloned()
+        .unwrap_or_else(|| input_json.with_file_name("error-probes.json"));
+    let out_file = OutputFile::new(&output_path, FileOutputFormat::Json);
+    out_file.write_json_output_file(json!(report));
+
+    print_error_probe_report(&report);
+    println!("Error probes saved to {}", output_path.display());
+}
+
+/// Reads the parse to probe, and the environment to run the program in: the
+/// one it was crawled with, unless another exec prefix is given
+fn load_probe_target(
+    input_json: &PathBuf,
+    exec_prefix: Option<ExecPrefix>,
+) -> Option<(serde_json::Value, CrawlEnvironment)> {
+    let structure: serde_json::Value = match fs::read_to_string(input_json)
+        .map_err(|e| e.to_string())
+        .and_then(|json| serde_json::from_str(&json).map_err(|e| e.to_string()))
+    {
+        Ok(structure) => structure,
+        Err(e) => {
+            println!("Failed to read {}: {}", input_json.display(), e);
+            return None;
+        }
+    };
+    let mut environment = CrawlEnvironment::from_metadata(&structure["metadata"]["environment"]);
+    if exec_prefix.is_some() {
+        environment.exec_prefix = exec_prefix;
+    }
+    Some((structure, environment))
+}
+
+/// Tries typos of every subcommand and long flag of a parse, and saves whether
+/// the program suggested the right correction
+pub fn run_typo_probe(
+    input_json: &PathBuf,
+    output_path: Option<&PathBuf>,
+    exec_prefix: Option<ExecPrefix>,
+) {
+    let Some((structure, environment)) = load_probe_target(input_json, exec_prefix) else {
+        return;
+    };
+    let report = probe_typos(&structure, &CrawlOptions::live(environment));
+
+    let output_path = output_path
+        .cloned()
+        .unwrap_or_else(|| input_json.with_file_name("typo-probes.json"));
+    let out_file = OutputFile::new(&output_path, FileOutputFormat::Json);
+    out_file.write_json_output_file(json!(report));
+
+    print_typo_coverage(&report);
+    println!("Typo probes saved to {}", output_path.display());
+}
+
+fn print_typo_coverage(report: &TypoProbeReport) {
+    let corrected = report
+        .results
+        .iter()
+        .filter(|result| result.outcome == TypoOutcome::Corrected)
+        .count();
+    println!(
+        "Typo probes: {} of {} typos corrected",
+        corrected,
+        report.results.len()
+    );
+    if report.coverage.is_empty() {
+        return;
+    }
+
+    let width = report
+        .coverage
+        .iter()
+        .map(|coverage| coverage.command_path.len())
+        .max()
+        .unwrap_or_default()
+        .max("Command".len());
+    let ratio = |corrected: usize, probed: usize| {
+        if probed == 0 {
+            "-".to_string()
+        } else {
+            format!("{}/{}", corrected, probed)
+        }
+    };
+    println!(
+        "  {:<width$}  {:>11}  {:>7}",
+        "Command", "Subcommands", "Flags"
+    );
+    for coverage in &report.coverage {
+        println!(
+            "  {:<width$}  {:>11}  {:>7}",
+            coverage.command_path,
+            ratio(coverage.subcommands_corrected, coverage.subcommands_probed),
+            ratio(coverage.flags_corrected, coverage.flags_probed)
+        );
+    }
+}
+
+/// Asks every command of a parse for help with `-h`, `--help`, `<prog> help`
+/// and no arguments, and saves where the answers differ
+pub fn run_help_paths_audit(
+    input_json: &PathBuf,
+    output_path: Option<&PathBuf>,
+    exec_prefix: Option<ExecPrefix>,
+) {
+    let Some((structure, environment)) = load_probe_target(input_json, exec_prefix) else {
+        return;
+    };
+    let audit = audit_help_paths(&structure, &CrawlOptions::live(environment));
+
+    let output_path = output_path
+        .cloned()
+        .unwrap_or_else(|| input_json.with_file_name("help-paths.json"));
+    let out_file = OutputFile::new(&output_path, FileOutputFormat::Json);
+    out_file.write_json_output_file(json!(audit));
+
+    print_help_paths_audit(&audit);
+    println!("Help path audit saved to {}", output_path.display());
+}
+
+fn print_help_paths_audit(audit: &HelpPathsAudit) {
+    let inconsistent: Vec<_> = audit
+        .commands
+        .iter()
+        .filter(|command| !command.consistent)
+        .collect();
+    println!(
+        "Help paths: {} commands, {} with inconsistent help",
+        audit.commands.len(),
+        inconsistent.len()
+    );
+
+    let lines: Vec<String> = inconsistent
+        .iter()
+        .flat_map(|command| &command.paths)
+        .flat_map(|path| {
+            path.differences
+                .iter()
+                .map(move |difference| format!("  ! {}: {}", path.invocation, difference))
+        })
+        .collect();
+    for line in lines.iter().take(CRAWL_REPORT_MAX_LINES) {
+        println!("{}", line);
+    }
+    if lines.len() > CRAWL_REPORT_MAX_LINES {
+        println!("  ... and {} more", lines.len() - CRAWL_REPORT_MAX_LINES);
+    }
+}
+
+pub fn print_lint_rules() {
+    for rule in &LINT_RULES {
+        println!(
+            "  {:<28} {:<8} {}",
+            rule.id,
+            format!("{:?}", rule.severity).to_lowercase(),
+            rule.description
+        );
+    }
+}
+
+/// Checks a parse against the CLI design rules and saves the findings. Exits
+/// with 1 when a finding reaches the `--fail-on` severity, so CI can gate on it.
+pub fn run_lint(
+    input_json: &PathBuf,
+    output_path: Option<&PathBuf>,
+    config_path: Option<&PathBuf>,
+    report_options: &ReportOptions,
+) {
+    let Some((structure, _)) = load_probe_target(input_json, None) else {
+        return;
+    };
+
+    let config_file = config_path.cloned().unwrap_or_else(|| {
+        default_lint_config_path(structure["name"].as_str().unwrap_or_default())
+    });
+    let config = if config_path.is_some() || config_file.exists() {
+        match load_lint_config(&config_file) {
+            Ok(config) => {
+                println!("Using lint config {}", config_file.display());
+                config
+            }
+            Err(e) => {
+                println!("{}", e);
+                return;
+            }
+        }
+    } else {
+        LintConfig::default()
+    };
+    for rule in unknown_rules(&config) {
+        println!("Warning: lint config names unknown rule '{}'", rule);
+    }
+
+    let report = lint_structure(&structure, &config);
+    let output_path = output_path
+        .cloned()
+        .unwrap_or_else(|| input_json.with_file_name("lint.json"));
+    let out_file = OutputFile::new(&output_path, FileOutputFormat::Json);
+    out_file.write_json_output_file(json!(report));
+
+    print_lint_report(&report);
+    println!("Lint results saved to {}", output_path.display());
+
+    if let Some(format) = report_options.format {
+        let report_path = report_options.path.clone().unwrap_or_else(|| {
+            input_json.with_file_name(format!("lint.{}", format.get_file_extension()))
+        });
+        let rule_descriptions: Vec<(&str, &str)> = LINT_RULES
+            .iter()
+            .map(|rule| (rule.id, rule.description))
+            .collect();
+        match write_report(
+            &report_path,
+            format,
+            "clint lint",
+            &structure,
+            &lint_results(&report, &structure),
+            &rule_descriptions,
+        ) {
+            Ok(()) => println!("Lint report saved to {}", report_path.display()),
+            Err(e) => println!("Failed to write lint report: {}", e),
+        }
+    }
+    if report
+        .findings
+        .iter()
+        .any(|finding| finding.severity.reaches(report_options.fail_on))
+    {
+        std::process::exit(1);
+    }
+}
+
+fn print_lint_report(report: &LintReport) {
+    let count = |severity| {
+        report
+            .findings
+            .iter()
+            .filter(|finding| finding.severity == severity)
+            .count()
+    };
+    println!(
+        "Lint: {} errors, {} warnings, {} info, {} suppressed",
+        count(Severity::Error),
+        count(Severity::Warning),
+        count(Severity::Info),
+        report.suppressed
+    );
+
+    let mut findings: Vec<_> = report.findings.iter().collect();
+    findings.sort_by_key(|finding| std::cmp::Reverse(finding.severity));
+    for finding in findings.iter().take(CRAWL_REPORT_MAX_LINES) {
+        println!(
+            "  {:?} [{}] {}: {}",
+            finding.severity, finding.rule, finding.command_path, finding.message
+        );
+    }
+    if findings.len() > CRAWL_REPORT_MAX_LINES {
+        println!("  ... and {} more", findings.len() - CRAWL_REPORT_MAX_LINES);
+    }
+}
+
+/// Groups the flags of a parse by name and short letter, and saves where the
+/// same flag means or takes different things with a suggested definition
+pub fn run_flag_analysis(input_json: &PathBuf, output_path: Option<&PathBuf>) {
+    let Some((structure, _)) = load_probe_target(input_json, None) else {
+        return;
+    };
+    let analysis = analyze_flags(&structure);
+
+    let output_path = output_path
+        .cloned()
+        .unwrap_or_else(|| input_json.with_file_name("flag-analysis.json"));
+    let out_file = OutputFile::new(&output_path, FileOutputFormat::Json);
+    out_file.write_json_output_file(json!(analysis));
+
+    print_flag_analysis(&analysis);
+    println!("Flag analysis saved to {}", output_path.display());
+}
+
+fn print_flag_analysis(analysis: &FlagAnalysis) {
+    println!(
+        "Flag analysis: {} flag definitions, {} conflicts",
+        analysis.occurrences,
+        analysis.conflicts.len()
+    );
+    let lines: Vec<String> = analysis
+        .conflicts
+        .iter()
+        .map(|conflict| {
+            let kind = serde_json::to_value(conflict.kind)
+                .ok()
+                .and_then(|kind| kind.as_str().map(|kind| kind.replace('_', " ")))
+                .unwrap_or_default();
+            format!("  ! {}: {}", kind, conflict.message)
+        })
+        .collect();
+    for line in lines.iter().take(CRAWL_REPORT_MAX_LINES) {
+        println!("{}", line);
+    }
+    if lines.len() > CRAWL_REPORT_MAX_LINES {
+        println!("  ... and {} more", lines.len() - CRAWL_REPORT_MAX_LINES);
+    }
+
+    let suggestions: Vec<&_> = analysis
+        .canonical
+        .iter()
+        .filter(|flag| !flag.deviating.is_empty())
+        .collect();
+    if suggestions.is_empty() {
+        return;
+    }
+    println!("Suggested definitions:");
+    for flag in suggestions.iter().take(CRAWL_REPORT_MAX_LINES) {
+        let short = flag
+            .short
+            .as_ref()
+            .map(|short| format!("{}, ", short))
+            .unwrap_or_default();
+        println!(
+            "  {}{} ({}): '{}', differs in {}",
+            short,
+            flag.long,
+            flag.kind,
+            flag.description,
+            flag.deviating.join(", ")
+        );
+    }
+    if suggestions.len() > CRAWL_REPORT_MAX_LINES {
+        println!(
+            "  ... and {} more",
+            suggestions.len() - CRAWL_REPORT_MAX_LINES
+        );
+    }
+}
+
+/// Prints every command's help page at several terminal widths and with
+/// colour-capable terminals, and saves the long lines and escape codes found
+pub fn run_terminal_audit(
+    input_json: &PathBuf,
+    output_path: Option<&PathBuf>,
+    exec_prefix: Option<ExecPrefix>,
+    max_line_length: usize,
+) {
+    let Some((structure, environment)) = load_probe_target(input_json, exec_prefix) else {
+        return;
+    };
+    let audit = audit_terminal(&structure, &environment, max_line_length);
+
+    let output_path = output_path
+        .cloned()
+        .unwrap_or_else(|| input_json.with_file_name("terminal-audit.json"));
+    let out_file = OutputFile::new(&output_path, FileOutputFormat::Json);
+    out_file.write_json_output_file(json!(audit));
+
+    print_terminal_audit(&audit);
+    println!("Terminal audit saved to {}", output_path.display());
+}
+
+fn print_terminal_audit(audit: &TerminalAudit) {
+    let with_findings = audit
+        .commands
+        .iter()
+        .filter(|command| !command.findings.is_empty())
+        .count();
+    println!(
+        "Terminal audit: {} commands, {} with findings",
+        audit.commands.len(),
+        with_findings
+    );
+
+    let lines: Vec<String> = audit
+        .commands
+        .iter()
+        .flat_map(|command| {
+            command.findings.iter().map(move |finding| {
+                format!(
+                    "  ! {} ({}): {}",
+                    command.command_path, finding.environment, finding.message
+                )
+            })
+        })
+        .collect();
+    for line in lines.iter().take(CRAWL_REPORT_MAX_LINES) {
+        println!("{}", line);
+    }
+    if lines.len() > CRAWL_REPORT_MAX_LINES {
+        println!("  ... and {} more", lines.len() - CRAWL_REPORT_MAX_LINES);
+    }
+}
+
+fn print_error_probe_report(report: &ErrorProbeReport) {
+    let results = &report.results;
+    let accepted = results.iter().filter(|result| result.status == 0).count();
+    let without_help = results.iter().filter(|r| !r.suggests_help).count();
+    let without_fix = results.iter().filter(|r| !r.suggests_fix).count();
+    println!(
+        "Error probes: {} invocations, {} accepted (exit 0), {} without a --help hint, {} without a fix hint",
+        results.len(),
+        accepted,
+        without_help,
+        without_fix
+    );
+
+    let lines: Vec<String> = results
+        .iter()
+        .filter(|result| result.status == 0 || (!result.suggests_help && !result.suggests_fix))
+        .map(|result| {
+            let problem = if result.status == 0 {
+                "accepted"
+            } else {
+                "no hint"
+            };
+            format!("  ! {} ({})", result.invocation, problem)
+        })
+        .collect();
+    for line in lines.iter().take(CRAWL_REPORT_MAX_LINES) {
+        println!("{}", line);
+    }
+    if lines.len() > CRAWL_REPORT_MAX_LINES {
+        println!("  ... and {} more", lines.len() - CRAWL_REPORT_MAX_LINES);
+    }
+}
+
+fn print_latency_profile(profile: &LatencyProfile) {
+    let Some(slowest) = profile
+        .commands
+        .iter()
+        .max_by(|a, b| a.median_ms.total_cmp(&b.median_ms))
+    else {
+        return;
+    };
+    let mut medians: Vec<f64> = profile.commands.iter().map(|c| c.median_ms).collect();
+    medians.sort_by(f64::total_cmp);
+    println!(
+        "Latency profile: {} commands x {} runs, median {:.1} ms, slowest {} ({:.1} ms median, {:.1} ms p95)",
+        profile.commands.len(),
+        profile.runs,
+        medians[medians.len() / 2],
+        slowest.command_path,
+        slowest.median_ms,
+        slowest.p95_ms
+    );
+
+    let outliers: Vec<_> = profile.commands.iter().filter(|c| c.outlier).collect();
+    for command in outliers.iter().take(CRAWL_REPORT_MAX_LINES) {
+        println!(
+            "  ! {}: {:.1} ms median, siblings {:.1} ms",
+            command.command_path,
+            command.median_ms,
+            command.sibling_median_ms.unwrap_or_default()
+        );
+    }
+    if outliers.len() > CRAWL_REPORT_MAX_LINES {
+        println!("  ... and {} more", outliers.len() - CRAWL_REPORT_MAX_LINES);
+    }
+}
+
+fn print_incremental_report(report: &IncrementalReport) {
+    println!(
+        "Incremental parse: {} refreshed, {} added, {} removed, {} unchanged",
+        report.refreshed.len(),
+        report.added.len(),
+        report.removed.len(),
+        report.unchanged
+    );
+    for command_path in &report.refreshed {
+        println!("  ~ {}", command_path);
+    }
+    for command_path in &report.added {
+        println!("  + {}", command_path);
+    }
+    for command_path in &report.removed {
+        println!("  - {}", command_path);
+    }
+}
+
 pub fn run_keyword_extractor(
     input_json: &PathBuf,
     output_path: &std::path::Path,
     format: FileOutputFormat,
+    min_confidence: Option<f64>,
 ) {
-    let keywords = extract_keywords_from_json(input_json).expect("Failed to analyze CLI JSON");
+    let keywords =
+        extract_keywords_from_json(input_json, min_confidence).expect("Failed to analyze CLI JSON");
     let out_file: OutputFile = OutputFile::new(output_path, format);
 
     match out_file.format {
@@ -448,14 +1101,15 @@ pub fn run_summary_generator(
     input_json: &PathBuf,
     output_path: &std::path::Path,
     format: FileOutputFormat,
+    min_confidence: Option<f64>,
 ) {
-    let summary = generate_summary(input_json).expect("Failed to analyze CLI JSON");
+    let summary = generate_summary(input_json, min_confidence).expect("Failed to analyze CLI JSON");
     let out_file: OutputFile = OutputFile::new(output_path, format);
 
     match out_file.format {
         FileOutputFormat::Markdown => {
             let summary_md = format!(
-                "# CLI Summary\n\n## Unique Keywords Count\n\n{}\n\n## Unique Command Count\n\n{}\n\n## Unique Subcommand Count\n\n{}\n\n## Unique Short Flag Count\n\n{}\n\n## Unique Long Flag Count\n\n{}\n\n## Total Command Count\n\n{}\n\n## Total Subcommand Count\n\n{}\n\n## Total Short Flag Count\n\n{}\n\n## Total Long Flag Count\n\n{}",
+                "# CLI Summary\n\n## Unique Keywords Count\n\n{}\n\n## Unique Command Count\n\n{}\n\n## Unique Subcommand Count\n\n{}\n\n## Unique Short Flag Count\n\n{}\n\n## Unique Long Flag Count\n\n{}\n\n## Total Command Count\n\n{}\n\n## Total Subcommand Count\n\n{}\n\n## Total Short Flag Count\n\n{}\n\n## Total Long Flag Count\n\n{}\n\n## Total Alias Count\n\n{}\n\n## Total Example Count\n\n{}\n\n## Total Help Topic Count\n\n{}\n\n## Total Argument Count\n\n{}",
                 summary.unique_keywords_count,
                 summary.unique_command_count,
                 summary.unique_subcommand_count,
@@ -464,7 +1118,11 @@ pub fn run_summary_generator(
                 summary.total_command_count,
                 summary.total_subcommand_count,
                 summary.total_short_flag_count,
-                summary.total_long_flag_count
+                summary.total_long_flag_count,
+                summary.total_alias_count,
+                summary.total_example_count,
+                summary.total_help_topic_count,
+                summary.total_argument_count
             );
             out_file.write_markdown_output(&summary_md.to_string());
         }
@@ -479,12 +1137,16 @@ pub fn run_summary_generator(
                 "total_subcommand_count": summary.total_subcommand_count,
                 "total_short_flag_count": summary.total_short_flag_count,
                 "total_long_flag_count": summary.total_long_flag_count,
+                "total_alias_count": summary.total_alias_count,
+                "total_example_count": summary.total_example_count,
+                "total_help_topic_count": summary.total_help_topic_count,
+                "total_argument_count": summary.total_argument_count,
             });
             out_file.write_json_output_file(summary_json);
         }
         FileOutputFormat::Text => {
             let summary_txt = format!(
-                "Unique Keywords Count: {}\n\nUnique Command Count: {}\n\nUnique Subcommand Count: {}\n\nUnique Short Flag Count: {}\n\nUnique Long Flag Count: {}\n\nTotal Command Count: {}\n\nTotal Subcommand Count: {}\n\nTotal Short Flag Count: {}\n\nTotal Long Flag Count: {}",
+                "Unique Keywords Count: {}\n\nUnique Command Count: {}\n\nUnique Subcommand Count: {}\n\nUnique Short Flag Count: {}\n\nUnique Long Flag Count: {}\n\nTotal Command Count: {}\n\nTotal Subcommand Count: {}\n\nTotal Short Flag Count: {}\n\nTotal Long Flag Count: {}\n\nTotal Alias Count: {}\n\nTotal Example Count: {}\n\nTotal Help Topic Count: {}\n\nTotal Argument Count: {}",
                 summary.unique_keywords_count,
                 summary.unique_command_count,
                 summary.unique_subcommand_count,
@@ -493,13 +1155,17 @@ pub fn run_summary_generator(
                 summary.total_command_count,
                 summary.total_subcommand_count,
                 summary.total_short_flag_count,
-                summary.total_long_flag_count
+                summary.total_long_flag_count,
+                summary.total_alias_count,
+                summary.total_example_count,
+                summary.total_help_topic_count,
+                summary.total_argument_count
             );
             out_file.write_plain_output(&summary_txt.to_string());
         }
         FileOutputFormat::Csv => {
             let csv_content = format!(
-                "metric,value\nunique_keywords_count,{}\nunique_command_count,{}\nunique_subcommand_count,{}\nunique_short_flag_count,{}\nunique_long_flag_count,{}\ntotal_command_count,{}\ntotal_subcommand_count,{}\ntotal_short_flag_count,{}\ntotal_long_flag_count,{}\n",
+                "metric,value\nunique_keywords_count,{}\nunique_command_count,{}\nunique_subcommand_count,{}\nunique_short_flag_count,{}\nunique_long_flag_count,{}\ntotal_command_count,{}\ntotal_subcommand_count,{}\ntotal_short_flag_count,{}\ntotal_long_flag_count,{}\ntotal_alias_count,{}\ntotal_example_count,{}\ntotal_help_topic_count,{}\ntotal_argument_count,{}\n",
                 summary.unique_keywords_count,
                 summary.unique_command_count,
                 summary.unique_subcommand_count,
@@ -508,7 +1174,11 @@ pub fn run_summary_generator(
                 summary.total_command_count,
                 summary.total_subcommand_count,
                 summary.total_short_flag_count,
-                summary.total_long_flag_count
+                summary.total_long_flag_count,
+                summary.total_alias_count,
+                summary.total_example_count,
+                summary.total_help_topic_count,
+                summary.total_argument_count
             );
             out_file.write_csv_output(&csv_content);
         }
@@ -519,6 +1189,8 @@ pub fn run_interactive_serve(
     template: Option<&String>,
     port: Option<u16>,
     input_file: Option<&PathBuf>,
+    min_confidence: Option<f64>,
+    variant: Option<&String>,
 ) {
     let home_dir = env::var("HOME")
         .or_else(|_| env::var("USERPROFILE"))
@@ -526,7 +1198,7 @@ pub fn run_interactive_serve(
 
     // Check if specific input file is provided
     if let Some(input_path) = input_file {
-        serve_specific_file(input_path, template, port);
+        serve_specific_file(input_path, template, port, min_confidence, variant);
         return;
     }
 
@@ -542,10 +1214,16 @@ pub fn run_interactive_serve(
         return;
     }
 
-    serve_with_interactive_selection(&parsed_dir, port);
+    serve_with_interactive_selection(&parsed_dir, port, min_confidence, variant);
 }
 
-fn serve_specific_file(input_path: &PathBuf, template: Option<&String>, port: Option<u16>) {
+fn serve_specific_file(
+    input_path: &PathBuf,
+    template: Option<&String>,
+    port: Option<u16>,
+    min_confidence: Option<f64>,
+    variant: Option<&String>,
+) {
     let home_dir = env::var("HOME")
         .or_else(|_| env::var("USERPROFILE"))
         .expect("Could not find home directory");
@@ -668,10 +1346,17 @@ fn serve_specific_file(input_path: &PathBuf, template: Option<&String>, port: Op
         app_name.to_string(),
         version,
         port,
+        min_confidence,
+        variant,
     ));
 }
 
-fn serve_with_interactive_selection(parsed_dir: &PathBuf, port: Option<u16>) {
+fn serve_with_interactive_selection(
+    parsed_dir: &PathBuf,
+    port: Option<u16>,
+    min_confidence: Option<f64>,
+    variant: Option<&String>,
+) {
     // Get all directories with JSON files
     let mut apps_with_data = Vec::new();
 
@@ -830,15 +1515,41 @@ fn serve_with_interactive_selection(parsed_dir: &PathBuf, port: Option<u16>) {
         selected_app.clone(),
         selected_version,
         port,
+        min_confidence,
+        variant,
     ));
 }
 
+/// Narrows the served structure down to one variant, and flags what scores
+/// below the confidence threshold for the web interface to highlight
+fn prepare_served_structure(
+    json_content: String,
+    min_confidence: Option<f64>,
+    variant: Option<&String>,
+) -> String {
+    if min_confidence.is_none() && variant.is_none() {
+        return json_content;
+    }
+    let Ok(mut structure) = serde_json::from_str::<serde_json::Value>(&json_content) else {
+        return json_content;
+    };
+    if let Some(variant) = variant {
+        filter_by_variant(&mut structure, variant);
+    }
+    if let Some(min_confidence) = min_confidence {
+        mark_low_confidence(&mut structure, min_confidence);
+    }
+    structure.to_string()
+}
+
 async fn start_http_server(
     json_path: PathBuf,
     template_path: PathBuf,
     app_name: String,
     version: String,
     port: Option<u16>,
+    min_confidence: Option<f64>,
+    variant: Option<&String>,
 ) {
     // Read the JSON content
     let json_content = match fs::read_to_string(&json_path) {
@@ -848,6 +1559,7 @@ async fn start_http_server(
             return;
         }
     };
+    let json_content = prepare_served_structure(json_content, min_confidence, variant);
     let json_to_serve_path = match json_path.clone().to_str() {
         Some(path) => path.to_string(),
         None => "unknown path".to_string(),
@@ -1007,9 +1719,16 @@ pub fn run_cli_replicator(
     output_path: &PathBuf,
     keep_help_flags: bool,
     keep_verbose_flags: bool,
+    min_confidence: Option<f64>,
 ) {
-    replicator::replicate(input_json, output_path, keep_help_flags, keep_verbose_flags)
-        .expect("Failed to replicate CLI");
+    replicator::replicate(
+        input_json,
+        output_path,
+        keep_help_flags,
+        keep_verbose_flags,
+        min_confidence,
+    )
+    .expect("Failed to replicate CLI");
 }
 
 fn generate_json_schema(output_path: &PathBuf) {
@@ -1136,7 +1855,7 @@ fn generate_command_file(
                 .is_some_and(|flags| !flags.is_empty());
 
             // Check for arguments
-            let has_arguments = has_usage_arguments(children)
+            let has_arguments = has_arguments(command_data)
                 || children
                     .get("ARGUMENT")
                     .and_then(|v| v.as_array())
@@ -1164,7 +1883,7 @@ fn generate_command_file(
                 .is_some_and(|flags| !flags.is_empty());
 
             // Check for arguments
-            let has_arguments = has_usage_arguments(children)
+            let has_arguments = has_arguments(command_data)
                 || children
                     .get("ARGUMENT")
                     .and_then(|v| v.as_array())
@@ -1259,50 +1978,61 @@ fn generate_command_file(
     if let Some(children) = command_data.get("children").and_then(|v| v.as_object()) {
         let mut arguments = Vec::new();
 
-        // Extract arguments from USAGE array
-        if let Some(usage_array) = children.get("USAGE").and_then(|v| v.as_array()) {
-            for usage in usage_array {
-                if let Some(usage_components) =
-                    usage.get("usage_components").and_then(|v| v.as_array())
-                {
-                    for component in usage_components {
-                        if let Some(component_type) =
-                            component.get("component_type").and_then(|v| v.as_str())
-                            && let Some(name) = component.get("name").and_then(|v| v.as_str())
-                            && component_type == "Keyword"
-                            && name.chars().all(|c| c.is_uppercase() || c == '_')
-                            && name != "FLAGS"
-                        // Exclude FLAGS keyword
-                        {
-                            let is_required = component
-                                .get("required")
-                                .and_then(|v| v.as_bool())
-                                .unwrap_or(true);
-                            arguments.push((name.to_string(), is_required));
-                        }
-                    }
+        // Positional arguments derived by the parser. Structures parsed before
+        // `arguments` existed fall back to the upper-case keywords of the usage.
+        if let Some(command_arguments) = command_data.get("arguments").and_then(|v| v.as_array()) {
+            for argument in command_arguments {
+                if let Some(name) = argument.get("name").and_then(|v| v.as_str()) {
+                    arguments.push(TsArgument {
+                        name: name.to_string(),
+                        description: argument
+                            .get("description")
+                            .and_then(|v| v.as_str())
+                            .unwrap_or(name)
+                            .to_string(),
+                        required: argument
+                            .get("required")
+                            .and_then(|v| v.as_bool())
+                            .unwrap_or(true),
+                        variadic: argument
+                            .get("variadic")
+                            .and_then(|v| v.as_bool())
+                            .unwrap_or(false),
+                    });
                 }
             }
+        } else {
+            arguments.extend(usage_arguments(children));
         }
 
         // Also check legacy ARGUMENT array for backwards compatibility
         if let Some(args) = children.get("ARGUMENT").and_then(|v| v.as_array()) {
             for (i, arg) in args.iter().enumerate() {
-                if let Some(arg_str) = arg.as_str() {
-                    arguments.push((arg_str.to_string(), true));
-                } else {
-                    arguments.push((format!("arg{}", i), true));
-                }
+                let name = arg
+                    .as_str()
+                    .map_or_else(|| format!("arg{}", i), |s| s.to_string());
+                arguments.push(TsArgument {
+                    description: name.clone(),
+                    name,
+                    required: true,
+                    variadic: false,
+                });
             }
         }
 
         if !arguments.is_empty() {
             content.push_str("  arguments: {\n");
-            for (arg_name, is_required) in arguments {
-                let arg_variable_name = sanitize_js_variable_name(&arg_name.to_lowercase());
+            for argument in arguments {
+                let arg_variable_name = sanitize_js_variable_name(&argument.name.to_lowercase());
                 content.push_str(&format!("    {}: {{\n", arg_variable_name));
-                content.push_str(&format!("      description: '{}',\n", arg_name));
-                content.push_str(&format!("      required: {},\n", is_required));
+                content.push_str(&format!(
+                    "      description: '{}',\n",
+                    escape_string(&argument.description)
+                ));
+                content.push_str(&format!("      required: {},\n", argument.required));
+                if argument.variadic {
+                    content.push_str("      variadic: true,\n");
+                }
                 content.push_str("      valueDataType: CommandComponentDataType.STRING,\n");
                 content.push_str("      formats: [\n");
                 content.push_str("        {\n");
@@ -1375,7 +2105,28 @@ fn generate_command_file(
     index_content.push_str(&format!("export * from '{}';\n", export_path));
 }
 
-fn has_usage_arguments(children: &serde_json::Map<String, serde_json::Value>) -> bool {
+/// A positional argument as emitted into the generated command file
+struct TsArgument {
+    name: String,
+    description: String,
+    required: bool,
+    variadic: bool,
+}
+
+fn has_arguments(command_data: &serde_json::Value) -> bool {
+    match command_data.get("arguments").and_then(|v| v.as_array()) {
+        Some(arguments) => !arguments.is_empty(),
+        None => command_data
+            .get("children")
+            .and_then(|v| v.as_object())
+            .is_some_and(|children| !usage_arguments(children).is_empty()),
+    }
+}
+
+/// Arguments read from the upper-case keywords of the USAGE components, for
+/// structures parsed before the parser derived `arguments`
+fn usage_arguments(children: &serde_json::Map<String, serde_json::Value>) -> Vec<TsArgument> {
+    let mut arguments = Vec::new();
     if let Some(usage_array) = children.get("USAGE").and_then(|v| v.as_array()) {
         for usage in usage_array {
             if let Some(usage_components) = usage.get("usage_components").and_then(|v| v.as_array())
@@ -1389,13 +2140,102 @@ fn has_usage_arguments(children: &serde_json::Map<String, serde_json::Value>) ->
                         && name != "FLAGS"
                     // Exclude FLAGS keyword
                     {
-                        return true;
+                        arguments.push(TsArgument {
+                            name: name.to_string(),
+                            description: name.to_string(),
+                            required: component
+                                .get("required")
+                                .and_then(|v| v.as_bool())
+                                .unwrap_or(true),
+                            variadic: false,
+                        });
                     }
                 }
             }
         }
     }
-    false
+    arguments
+}
+
+/// Flag details the parser extracts into structured fields
+struct FlagMetadata {
+    choices: Vec<String>,
+    env: Vec<String>,
+    deprecated: bool,
+    negates: Option<String>,
+    repeatable: bool,
+    value_placeholder: Option<String>,
+}
+
+impl FlagMetadata {
+    fn from_flag(flag_obj: &serde_json::Map<String, serde_json::Value>) -> Self {
+        let strings = |key: &str| -> Vec<String> {
+            flag_obj
+                .get(key)
+                .and_then(|v| v.as_array())
+                .map(|values| {
+                    values
+                        .iter()
+                        .filter_map(|v| v.as_str())
+                        .map(|s| s.to_string())
+                        .collect()
+                })
+                .unwrap_or_default()
+        };
+        FlagMetadata {
+            choices: strings("choices"),
+            env: strings("env"),
+            deprecated: flag_obj
+                .get("deprecated")
+                .and_then(|v| v.as_bool())
+                .unwrap_or(false),
+            negates: flag_obj
+                .get("negates")
+                .and_then(|v| v.as_str())
+                .map(|s| s.to_string()),
+            repeatable: flag_obj
+                .get("repeatable")
+                .and_then(|v| v.as_bool())
+                .unwrap_or(false),
+            value_placeholder: flag_obj
+                .get("value_placeholder")
+                .and_then(|v| v.as_str())
+                .map(|s| s.to_string()),
+        }
+    }
+
+    fn to_typescript_properties(&self) -> String {
+        let string_list = |values: &[String]| {
+            values
+                .iter()
+                .map(|v| format!("'{}'", escape_string(v)))
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+        let mut content = String::new();
+        if !self.choices.is_empty() {
+            content.push_str(&format!("    choices: [{}],\n", string_list(&self.choices)));
+        }
+        if !self.env.is_empty() {
+            content.push_str(&format!("    env: [{}],\n", string_list(&self.env)));
+        }
+        if self.deprecated {
+            content.push_str("    deprecated: true,\n");
+        }
+        if let Some(negates) = &self.negates {
+            content.push_str(&format!("    negates: '{}',\n", escape_string(negates)));
+        }
+        if self.repeatable {
+            content.push_str("    repeatable: true,\n");
+        }
+        if let Some(value_placeholder) = &self.value_placeholder {
+            content.push_str(&format!(
+                "    valuePlaceholder: '{}',\n",
+                escape_string(value_placeholder)
+            ));
+        }
+        content
+    }
 }
 
 fn generate_flags_constant(
@@ -1434,12 +2274,18 @@ fn generate_flags_constant(
                     .and_then(|v| v.as_str())
                     .unwrap_or("");
 
+                let metadata = FlagMetadata::from_flag(flag_obj);
+
                 // Extract and clean description by removing data type prefix
                 let (clean_description, extracted_data_type) =
                     extract_data_type_from_description(description);
 
-                // Determine data type based on data_type field, extracted type, or patterns
-                let data_type_enum = if !data_type.is_empty() {
+                // Determine data type based on Cobra's own type, then the
+                // placeholder, the extracted type, or patterns
+                let pflag_type = flag_obj.get("pflag_type").and_then(|v| v.as_str());
+                let data_type_enum = if let Some(pflag_type) = pflag_type {
+                    pflag_data_type(pflag_type, &clean_description)
+                } else if !data_type.is_empty() {
                     match data_type {
                         "stringArray" => {
                             // Check if it's actually key-value mapping based on description
@@ -1476,6 +2322,9 @@ fn generate_flags_constant(
                 } else if long_flag == "--help"
                     || clean_description.starts_with("help for")
                     || long_flag.starts_with("--no-")
+                    // A parsed flag without a value placeholder takes no value
+                    || (flag_obj.contains_key("value_placeholder")
+                        && metadata.value_placeholder.is_none())
                 {
                     "CommandComponentDataType.BOOLEAN"
                 } else {
@@ -1483,16 +2332,16 @@ fn generate_flags_constant(
                     "CommandComponentDataType.STRING"
                 };
 
-                // Extract default value from description
-                let default_value = extract_default_value(&clean_description);
+                let default_value = flag_obj
+                    .get("default")
+                    .and_then(|v| v.as_str())
+                    .map(|s| s.to_string());
 
                 // Extract examples (if any) from description
                 let examples = extract_examples(&clean_description);
 
                 // Determine if flag is required based on description patterns and usage string
-                let is_required = if description.contains("(default")
-                    || description.to_lowercase().contains("default is")
-                    || description.to_lowercase().contains("defaults to")
+                let is_required = if default_value.is_some()
                     || long_flag == "--help"
                     || description.starts_with("help for")
                 {
@@ -1512,6 +2361,12 @@ fn generate_flags_constant(
                     check_flag_in_usage_string(usage_string, long_flag, short_flag)
                 };
 
+                let data_type_enum = if metadata.choices.is_empty() {
+                    data_type_enum
+                } else {
+                    "CommandComponentDataType.OPTION_LIST"
+                };
+
                 flag_objects.push((
                     long_flag.to_string(),
                     short_flag.to_string(),
@@ -1520,6 +2375,7 @@ fn generate_flags_constant(
                     default_value,
                     examples,
                     is_required,
+                    metadata,
                 ));
             }
         }
@@ -1544,6 +2400,7 @@ fn generate_flags_constant(
             default_value,
             examples,
             is_required,
+            metadata,
         ) in &flag_objects
         {
             content.push_str("  {\n");
@@ -1573,6 +2430,8 @@ fn generate_flags_constant(
                 ));
             }
 
+            content.push_str(&metadata.to_typescript_properties());
+
             // Formats array (similar to arguments)
             content.push_str("    formats: [\n");
             content.push_str("      {\n");
@@ -1603,6 +2462,7 @@ fn generate_flags_constant(
                 "CommandComponentDataType.FLOAT" => "NamingConventions.Float()",
                 "CommandComponentDataType.BOOLEAN" => "NamingConventions.Boolean()",
                 "CommandComponentDataType.KEY_VALUE_MAPPING" => "NamingConventions.KeyValue()",
+                "CommandComponentDataType.OPTION_LIST" => "NamingConventions.String()",
                 "[CommandComponentDataType.STRING]" => "NamingConventions.StringArray()",
                 _ => "NamingConventions.String()",
             };
@@ -1666,6 +2526,27 @@ fn escape_string(s: &str) -> String {
         .replace('\r', "\\r")
 }
 
+/// The exported data type for a type name printed by pflag (Cobra)
+fn pflag_data_type(pflag_type: &str, description: &str) -> &'static str {
+    match pflag_type {
+        "stringArray" | "stringSlice" | "strings" if is_key_value_mapping(description) => {
+            "CommandComponentDataType.KEY_VALUE_MAPPING"
+        }
+        "stringArray" | "stringSlice" | "strings" | "durationSlice" => {
+            "[CommandComponentDataType.STRING]"
+        }
+        "stringToString" | "stringToInt" => "CommandComponentDataType.KEY_VALUE_MAPPING",
+        "int" | "int8" | "int16" | "int32" | "int64" | "uint" | "uint8" | "uint16" | "uint32"
+        | "uint64" | "count" => "CommandComponentDataType.INTEGER",
+        "ints" | "intSlice" | "uints" => "[CommandComponentDataType.INTEGER]",
+        "float" | "float32" | "float64" => "CommandComponentDataType.FLOAT",
+        "bool" => "CommandComponentDataType.BOOLEAN",
+        "bools" => "[CommandComponentDataType.BOOLEAN]",
+        // string, duration, ip, ipNet, bytesHex
+        _ => "CommandComponentDataType.STRING",
+    }
+}
+
 fn extract_data_type_from_description(description: &str) -> (String, String) {
     // Extract data type prefix from description like "uint Number of servers..."
     let data_type_prefixes = [
@@ -1690,28 +2571,6 @@ fn extract_data_type_from_description(description: &str) -> (String, String) {
     (description.to_string(), String::new())
 }
 
-fn extract_default_value(description: &str) -> Option<String> {
-    // Extract default value from patterns like "(default 1)" or "default is nvidia"
-    if let Some(start) = description.find("(default ")
-        && let Some(end) = description[start..].find(')')
-    {
-        let default_part = &description[start + 9..start + end]; // Skip "(default "
-        return Some(default_part.to_string());
-    }
-
-    if let Some(start) = description.find("default is ") {
-        let default_part = description[start + 11..]
-            .split_whitespace()
-            .next()
-            .unwrap_or("");
-        if !default_part.is_empty() {
-            return Some(default_part.to_string());
-        }
-    }
-
-    None
-}
-
 fn extract_examples(_description: &str) -> Vec<String> {
     // Extract examples from description - this could be enhanced based on patterns found
     // For now, return empty as most descriptions don't have explicit examples
@@ -1786,6 +2645,8 @@ pub fn run_cli_compare(
     from_tag: Option<&String>,
     to_tag: Option<&String>,
     format: Option<&String>,
+    variant: Option<&String>,
+    report_options: &ReportOptions,
 ) {
     use crate::models::ParseOutputFormat;
 
@@ -1891,10 +2752,24 @@ pub fn run_cli_compare(
 
     match compare_format {
         ParseOutputFormat::TypeScriptDirectory => {
+            if variant.is_some() {
+                println!(
+                    "Warning: --variant only applies to json comparisons, comparing everything"
+                );
+            }
+            if report_options.format.is_some() || report_options.fail_on != Severity::Off {
+                println!("Warning: --report and --fail-on only apply to json comparisons");
+            }
             compare_typescript_directories(&from_path, &to_path, &from_version, &to_version);
         }
         _ => {
-            compare_json_files(&from_path, &to_path, &from_version, &to_version);
+            compare_json_files(
+                &from_path,
+                &to_path,
+                (&from_version, &to_version),
+                variant,
+                report_options,
+            );
         }
     }
 }
@@ -1903,10 +2778,11 @@ pub fn run_cli_compare(
 fn compare_json_files(
     from_path: &PathBuf,
     to_path: &PathBuf,
-    from_version: &str,
-    to_version: &str,
+    (from_version, to_version): (&str, &str),
+    variant: Option<&String>,
+    report_options: &ReportOptions,
 ) {
-    match comparison::compare_json_structures(from_path, to_path) {
+    match comparison::compare_json_structures(from_path, to_path, variant.map(String::as_str)) {
         Ok(changes) => {
             if changes.is_empty() {
                 println!(
@@ -1932,6 +2808,23 @@ fn compare_json_files(
                     to_path.display()
                 );
             }
+
+            if let Some(format) = report_options.format {
+                write_compare_report(
+                    &changes,
+                    from_path,
+                    to_path,
+                    (from_version, to_version),
+                    format,
+                    report_options.path.as_ref(),
+                );
+            }
+            if changes
+                .iter()
+                .any(|change| change.severity().reaches(report_options.fail_on))
+            {
+                std::process::exit(1);
+            }
         }
         Err(e) => {
             println!("Error comparing JSON structures: {}", e);
@@ -1957,6 +2850,47 @@ fn compare_json_files(
     }
 }
 
+fn write_compare_report(
+    changes: &[comparison::ChangeType],
+    from_path: &PathBuf,
+    to_path: &PathBuf,
+    (from_version, to_version): (&str, &str),
+    format: ReportFormat,
+    report_path: Option<&PathBuf>,
+) {
+    // Parses live in ./out/<prog>/<version>/, so reports go next to the versions
+    let report_path = report_path.cloned().unwrap_or_else(|| {
+        from_path
+            .parent()
+            .and_then(Path::parent)
+            .unwrap_or(Path::new("."))
+            .join(format!(
+                "compare-{}-{}.{}",
+                from_version,
+                to_version,
+                format.get_file_extension()
+            ))
+    });
+    let read_structure = |path: &PathBuf| -> serde_json::Value {
+        fs::read_to_string(path)
+            .ok()
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default()
+    };
+    let (from, to) = (read_structure(from_path), read_structure(to_path));
+    match write_report(
+        &report_path,
+        format,
+        "clint compare",
+        &to,
+        &change_results(changes, &from, &to),
+        &[],
+    ) {
+        Ok(()) => println!("Compare report saved to {}", report_path.display()),
+        Err(e) => println!("Failed to write compare report: {}", e),
+    }
+}
+
 /// Compare two TypeScript directories and display differences
 fn compare_typescript_directories(
     from_path: &Path,
@@ -1,30 +1,209 @@
-use crate::{models::*, usage_parser::parse_usage_line};
+use crate::{
+    argument_parser::{derive_arguments, is_argument_section, parse_argument_line},
+    cassette::{record_invocation, replay_invocation},
+    confidence::score_structure,
+    flag_parser::parse_flag_line,
+    incremental::{find_node, help_page_hash, node_help_hash},
+    latency::profile_latency,
+    models::*,
+    plugins::{Plugin, discover_plugins},
+    usage_parser::{KnownFlags, parse_usage_line},
+};
 use regex::Regex;
 use serde_json::{Value, json};
-use std::collections::HashSet;
+use std::collections::{BTreeMap, HashSet};
+use std::fs;
 use std::process::Command;
+use std::sync::LazyLock;
+use std::time::Instant;
 
-fn execute_full_command(command: &str) -> Value {
-    let command_vec: Vec<&str> = command.split_whitespace().collect();
-    match Command::new(command_vec[0])
-        .args(&command_vec[1..])
-        .output()
+/// How many lines of stderr are kept in the crawl report
+const STDERR_EXCERPT_LINES: usize = 3;
+
+/// Section external subcommands are filed under, as they aren't in any help page
+pub const PLUGIN_HEADER: &str = "Plugins";
+
+/// Bookkeeping shared by every level of a crawl
+#[derive(Default)]
+struct CrawlState {
+    visited: HashSet<String>,
+    report: CrawlReport,
+}
+
+// CSI sequences (colours, cursor movement) and OSC sequences (hyperlinks, titles)
+static ANSI_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"\x1b\[[0-?]*[ -/]*[@-~]|\x1b\][^\x07\x1b]*(\x07|\x1b\\)").unwrap()
+});
+
+pub fn strip_ansi_codes(text: &str) -> String {
+    ANSI_RE.replace_all(text, "").to_string()
+}
+
+/// Runs the program, through the exec prefix if there is one. The invocation
+/// keeps the program's own arguments, so cassettes and checkpoints don't depend
+/// on how it was run.
+pub fn run_process(argv: &[String], environment: &CrawlEnvironment) -> Invocation {
+    let command_line = match &environment.exec_prefix {
+        Some(exec_prefix) => exec_prefix.wrap(argv),
+        None => argv.to_vec(),
+    };
+    let mut process = Command::new(&command_line[0]);
+    process
+        .args(&command_line[1..])
+        .envs(environment.env_vars());
+    if let Some(working_dir) = &environment.working_dir {
+        process.current_dir(working_dir);
+    }
+
+    let (stdout, stderr, status) = match process.output() {
+        Ok(output) => (
+            String::from_utf8_lossy(&output.stdout).to_string(),
+            String::from_utf8_lossy(&output.stderr).to_string(),
+            output.status.code().unwrap_or(-1),
+        ),
+        Err(e) => (String::new(), format!("Error executing command: {}", e), -1),
+    };
+
+    Invocation {
+        argv: argv.to_vec(),
+        env: environment.env_vars().into_iter().collect(),
+        working_dir: environment.working_dir.clone(),
+        stdout,
+        stderr,
+        status,
+    }
+}
+
+/// Serves captured help text for `<command path> --help`. Any other invocation,
+/// or a command without a captured page, fails like an unknown command would.
+fn read_captured_help(argv: &[String], execution_mode: &ExecutionMode) -> Invocation {
+    let command_path = argv
+        .split_last()
+        .filter(|(last, _)| *last == "--help")
+        .map(|(_, command_path)| command_path);
+    let help_text = match (execution_mode, command_path) {
+        (ExecutionMode::HelpText(text, _), Some(command_path)) if command_path.len() == 1 => {
+            Some(text.clone())
+        }
+        (ExecutionMode::HelpTextDir(dir), Some(command_path)) => {
+            fs::read_to_string(dir.join(format!("{}.txt", command_path.join(" ")))).ok()
+        }
+        _ => None,
+    };
+
+    let (stdout, stderr, status) = match help_text {
+        Some(text) => (text, String::new(), 0),
+        None => (
+            String::new(),
+            format!("No captured help text for `{}`", argv.join(" ")),
+            1,
+        ),
+    };
+    Invocation {
+        argv: argv.to_vec(),
+        env: BTreeMap::new(),
+        working_dir: None,
+        stdout,
+        stderr,
+        status,
+    }
+}
+
+/// Serves an invocation saved by an interrupted crawl, or runs the program and
+/// saves the invocation to the checkpoint
+fn run_or_resume(argv: &[String], options: &CrawlOptions) -> Invocation {
+    let Some(checkpoint) = &options.checkpoint else {
+        return run_process(argv, &options.environment);
+    };
+    let env: BTreeMap<String, String> = options.environment.env_vars().into_iter().collect();
+    if let Some(invocation) = checkpoint.resumed_invocation(argv, &env) {
+        return invocation.clone();
+    }
+    let invocation = run_process(argv, &options.environment);
+    checkpoint.save(&invocation);
+    invocation
+}
+
+pub fn execute_full_command(command: &str, options: &CrawlOptions) -> Value {
+    let argv: Vec<String> = command.split_whitespace().map(String::from).collect();
+    let invocation = match &options.execution_mode {
+        ExecutionMode::Live => run_or_resume(&argv, options),
+        ExecutionMode::Record(cassette_dir) => {
+            let invocation = run_or_resume(&argv, options);
+            record_invocation(cassette_dir, &invocation);
+            invocation
+        }
+        ExecutionMode::Replay(cassette_dir) => replay_invocation(cassette_dir, &argv),
+        ExecutionMode::HelpText(..) | ExecutionMode::HelpTextDir(_) => {
+            read_captured_help(&argv, &options.execution_mode)
+        }
+    };
+
+    json!({
+        "stdout": strip_ansi_codes(&invocation.stdout).trim().to_string(),
+        "stderr": strip_ansi_codes(&invocation.stderr).trim().to_string(),
+        "status": invocation.status
+    })
+}
+
+/// Runs `<command> --help` and adds the command to the crawl report. Returns the
+/// help output and the index of the command's report entry.
+fn crawl_help_page(
+    command: &str,
+    command_path: &str,
+    options: &CrawlOptions,
+    report: &mut CrawlReport,
+) -> (Value, usize) {
+    let invocation = format!("{} --help", command);
+    let started = Instant::now();
+    let help_output = execute_full_command(&invocation, options);
+    let duration_ms = started.elapsed().as_millis() as u64;
+
+    let status = help_output["status"].as_i64().unwrap_or(-1) as i32;
+    let stderr = help_output["stderr"].as_str().unwrap_or_default();
+    let mut warnings = Vec::new();
+    if status == 0
+        && help_output["stdout"]
+            .as_str()
+            .unwrap_or_default()
+            .is_empty()
     {
-        Ok(output) => json!({
-            "stdout": String::from_utf8_lossy(&output.stdout).trim().to_string(),
-            "stderr": String::from_utf8_lossy(&output.stderr).trim().to_string(),
-            "status": output.status.code().unwrap_or(-1)
-        }),
-        Err(e) => json!({
-            "stdout": "",
-            "stderr": format!("Error executing command: {}", e),
-            "status": -1
+        warnings.push("help page is empty".to_string());
+    }
+
+    report.nodes.push(NodeReport {
+        command_path: command_path.to_string(),
+        invocation,
+        duration_ms,
+        status,
+        stderr_excerpt: (!stderr.is_empty()).then(|| {
+            stderr
+                .lines()
+                .take(STDERR_EXCERPT_LINES)
+                .collect::<Vec<_>>()
+                .join("\n")
         }),
+        warnings,
+        ..Default::default()
+    });
+    (help_output, report.nodes.len() - 1)
+}
+
+/// Copies what parsing a help page turned up into the command's report entry
+fn add_parse_findings(report: &mut CrawlReport, node_index: usize, parsed: &Value) {
+    let node_report = &mut report.nodes[node_index];
+    for (key, findings) in [
+        ("unclassified_lines", &mut node_report.unclassified_lines),
+        ("warnings", &mut node_report.warnings),
+    ] {
+        if let Some(lines) = parsed.get(key).and_then(Value::as_array) {
+            findings.extend(lines.iter().filter_map(|l| l.as_str().map(String::from)));
+        }
     }
 }
 
-fn get_program_version(program_name: &str) -> String {
-    let version_output = execute_full_command(&format!("{} version", program_name));
+fn get_program_version(program_name: &str, options: &CrawlOptions) -> String {
+    let version_output = execute_full_command(&format!("{} version", program_name), options);
     version_output
         .get("stdout")
         .and_then(|v| v.as_str())
@@ -42,91 +221,154 @@ fn is_header_line(line: &str) -> bool {
     }
 }
 
-fn get_flag_line(raw_flag_vec: Vec<&str>, section_header_name: &str) -> LineFlag {
-    let mut short: Option<&str> = None;
-    let mut long: Option<&str> = None;
+const COMMAND_SECTION_HEADERS: [&str; 3] = ["commands", "available commands", "subcommands"];
 
-    let mut flag_part: Vec<&str> = Vec::new();
-    let mut description_parts: Vec<&str> = Vec::new();
+fn is_command_section(section_header: &str) -> bool {
+    COMMAND_SECTION_HEADERS
+        .iter()
+        .any(|&h| section_header.to_lowercase().contains(h))
+}
 
-    for (i, part) in raw_flag_vec.iter().enumerate() {
-        if part.starts_with("-") || part.contains(",") {
-            flag_part.push(*part);
-        } else {
-            description_parts.extend(&raw_flag_vec[i..]);
-            break;
-        }
-    }
+fn is_example_section(section_header: &str) -> bool {
+    section_header.to_lowercase().contains("example")
+}
 
-    let flag_definitions_string = flag_part.join(" ");
-    let flag_definitions: Vec<&str> = flag_definitions_string.split(", ").collect();
-    let flags_and_possible_type_vec: Vec<&str> = flag_definitions
+/// Sections whose lines the crawler knows how to classify
+fn is_known_section(section_header: &str) -> bool {
+    let header = section_header.to_lowercase();
+    ["usage", "flag", "option", "alias", "help topics"]
         .iter()
-        .flat_map(|s| s.split_whitespace())
-        .collect();
+        .any(|keyword| header.contains(keyword))
+        || is_command_section(section_header)
+        || is_example_section(section_header)
+        || is_argument_section(section_header)
+}
 
-    let actual_flags_vec: Vec<&str> = flags_and_possible_type_vec
-        .iter()
-        .filter(|f| f.starts_with("-"))
-        .copied()
-        .collect();
+fn indentation(line: &str) -> usize {
+    line.len() - line.trim_start().len()
+}
 
-    let data_type_vec: Vec<&str> = flags_and_possible_type_vec
-        .iter()
-        .filter(|f| !f.starts_with("-") && !f.trim().is_empty())
-        .copied()
-        .collect();
+/// Joins wrapped description lines onto the flag or command entry they belong to.
+///
+/// A continuation line is indented deeper than the entry above it and does not
+/// start with a dash, e.g. a wrapped Cobra flag description or a description
+/// printed on the line below the flag. Folding them up front lets every entry be
+/// classified as a single line instead of leaking the rest into `OTHER`.
+fn fold_continuation_lines(output: &str) -> Vec<String> {
+    let mut folded: Vec<String> = Vec::new();
+    let mut current_section_header: Option<String> = None;
+    let mut entry_indentation: Option<usize> = None;
 
-    let data_type = data_type_vec.first().copied();
+    for line in output.lines() {
+        let trimmed_line = line.trim();
 
-    if actual_flags_vec.len() == 1 {
-        if actual_flags_vec[0].starts_with("--") {
-            long = Some(actual_flags_vec[0]);
-        } else {
-            short = Some(actual_flags_vec[0]);
-        }
-    } else if actual_flags_vec.len() >= 2 {
-        let mut sorted_flags = actual_flags_vec.clone();
-        sorted_flags.sort_by_key(|f| f.len());
-
-        for flag in sorted_flags {
-            if flag.starts_with("--") && long.is_none() {
-                long = Some(flag);
-            } else if flag.starts_with("-") && !flag.starts_with("--") && short.is_none() {
-                short = Some(flag);
+        if trimmed_line.is_empty() || is_header_line(line) {
+            if !trimmed_line.is_empty() {
+                current_section_header = Some(trimmed_line.to_string());
             }
+            entry_indentation = None;
+            folded.push(line.to_string());
+            continue;
         }
-    }
 
-    let description = if !description_parts.is_empty() {
-        Some(description_parts.join(" "))
-    } else {
-        None
-    };
+        if current_section_header
+            .as_deref()
+            .is_some_and(is_example_section)
+        {
+            folded.push(line.to_string());
+            continue;
+        }
+
+        let line_indentation = indentation(line);
+        if let Some(entry_indent) = entry_indentation
+            && line_indentation > entry_indent
+            && !trimmed_line.starts_with('-')
+            && let Some(entry_line) = folded.last_mut()
+        {
+            // Joined with a column gap, so the text stays in the description column
+            entry_line.push_str("  ");
+            entry_line.push_str(trimmed_line);
+            continue;
+        }
 
-    LineFlag {
-        short: short.map(|s| s.to_string()),
-        long: long.map(|s| s.to_string()),
-        data_type: data_type.map(|s| s.to_string()),
-        description,
-        parent_header: section_header_name.to_string(),
+        let is_entry = trimmed_line.starts_with('-')
+            || current_section_header
+                .as_deref()
+                .is_some_and(|header| is_command_section(header) || is_argument_section(header));
+        entry_indentation = if is_entry {
+            Some(line_indentation)
+        } else {
+            None
+        };
+        folded.push(line.to_string());
     }
+
+    folded
 }
 
 fn parse_child_line(
     command: &str,
     line: &str,
     section_header_name: Option<&str>,
+    known_flags: &KnownFlags,
 ) -> Option<ChildLine> {
     let section_header = section_header_name.unwrap_or("None");
     let trimmed_line = line.trim();
 
+    if is_example_section(section_header) {
+        return Some(ChildLine {
+            line_type: OutputLine::Example(LineExample {
+                line_contents: trimmed_line.to_string(),
+                parent_header: section_header.to_string(),
+            }),
+        });
+    }
+
+    if section_header.to_lowercase().contains("alias") {
+        let aliases = trimmed_line
+            .split(',')
+            .map(|alias| alias.trim().to_string())
+            .filter(|alias| !alias.is_empty() && alias != command)
+            .collect();
+        return Some(ChildLine {
+            line_type: OutputLine::Aliases(LineAliases {
+                aliases,
+                parent_header: section_header.to_string(),
+            }),
+        });
+    }
+
+    if section_header.to_lowercase().contains("help topics") {
+        // Cobra prints the full command path of each topic, padded to a column
+        let column_re = Regex::new(r"\s{2,}").unwrap();
+        let mut columns = column_re.splitn(trimmed_line, 2);
+        let command_path = columns.next().unwrap_or_default().to_string();
+        let description = columns.next().unwrap_or_default().to_string();
+        let name = command_path
+            .split_whitespace()
+            .last()
+            .unwrap_or_default()
+            .to_string();
+        return Some(ChildLine {
+            line_type: OutputLine::HelpTopic(LineHelpTopic {
+                name,
+                description,
+                command_path,
+                parent_header: section_header.to_string(),
+            }),
+        });
+    }
+
     let flag_re = Regex::new(r"^\s*(-{1,2}\S+)").unwrap();
+    if is_argument_section(section_header) && !flag_re.is_match(trimmed_line) {
+        return Some(ChildLine {
+            line_type: OutputLine::Argument(parse_argument_line(trimmed_line, section_header)),
+        });
+    }
+
     if flag_re.is_match(trimmed_line) {
-        let re = Regex::new(r"\s+").unwrap();
-        let line_components: Vec<&str> = re.split(trimmed_line).collect();
         return Some(ChildLine {
-            line_type: OutputLine::Flag(get_flag_line(line_components, section_header)),
+            line_type: OutputLine::Flag(parse_flag_line(trimmed_line, section_header)),
         });
     }
 
@@ -134,8 +376,7 @@ fn parse_child_line(
     let line_components: Vec<&str> = re.split(trimmed_line).collect();
 
     if line_components.len() == 1 {
-        let parse_usage_line = parse_usage_line(trimmed_line, command);
-        let usage_components = parse_usage_line;
+        let usage_components = parse_usage_line(trimmed_line, command, known_flags).components;
         if usage_components.is_empty() {
             return None;
         }
@@ -150,31 +391,18 @@ fn parse_child_line(
 
     if line_components.len() >= 2 {
         if section_header.to_lowercase().contains("usage") {
-            let usage_components = parse_usage_line(trimmed_line, command);
+            let parsed_usage = parse_usage_line(trimmed_line, command, known_flags);
             return Some(ChildLine {
                 line_type: OutputLine::Usage(LineUsage {
                     usage_string: trimmed_line.to_string(),
                     parent_header: section_header.to_string(),
-                    usage_components,
-                }),
-            });
-        }
-
-        if section_header.to_lowercase().contains("example") {
-            return Some(ChildLine {
-                line_type: OutputLine::Other(LineOther {
-                    line_contents: line.to_string(),
-                    parent_header: section_header.to_string(),
-                    components: None,
+                    usage_components: parsed_usage.components,
+                    parse_errors: parsed_usage.errors,
                 }),
             });
         }
 
-        let command_headers = ["commands", "available commands", "subcommands"];
-        if command_headers
-            .iter()
-            .any(|&h| section_header.to_lowercase().contains(h))
-        {
+        if is_command_section(section_header) {
             let name = line_components[0].to_string();
             let description = line_components[1..].join(" ");
 
@@ -205,8 +433,9 @@ fn handle_child_line(
     command: &str,
     section_header_name: &str,
     line: &str,
+    known_flags: &KnownFlags,
 ) -> Option<(ChildLineType, String)> {
-    let child_line = parse_child_line(command, line, Some(section_header_name))?;
+    let child_line = parse_child_line(command, line, Some(section_header_name), known_flags)?;
 
     match child_line.line_type {
         OutputLine::Usage(usage) => Some((
@@ -221,6 +450,22 @@ fn handle_child_line(
             ChildLineType::Flag,
             serde_json::to_string(&flag).expect("Failed to serialize flag line"),
         )),
+        OutputLine::Aliases(aliases) => Some((
+            ChildLineType::Aliases,
+            serde_json::to_string(&aliases).expect("Failed to serialize aliases line"),
+        )),
+        OutputLine::Example(example) => Some((
+            ChildLineType::Example,
+            serde_json::to_string(&example).expect("Failed to serialize example line"),
+        )),
+        OutputLine::HelpTopic(help_topic) => Some((
+            ChildLineType::HelpTopic,
+            serde_json::to_string(&help_topic).expect("Failed to serialize help topic line"),
+        )),
+        OutputLine::Argument(argument) => Some((
+            ChildLineType::Argument,
+            serde_json::to_string(&argument).expect("Failed to serialize argument line"),
+        )),
         OutputLine::Other(other) => Some((
             ChildLineType::Other,
             serde_json::to_string(&other).expect("Failed to serialize 'other' line"),
@@ -228,26 +473,263 @@ fn handle_child_line(
     }
 }
 
+/// Flags listed anywhere on a help page, so usage lines printed above the flag
+/// sections can be read with them
+fn known_flags(lines: &[String]) -> KnownFlags {
+    let mut known_flags = KnownFlags::default();
+    for line in lines.iter().filter(|line| !is_header_line(line)) {
+        let trimmed_line = line.trim();
+        if !trimmed_line.starts_with('-') {
+            continue;
+        }
+        let flag = parse_flag_line(trimmed_line, "");
+        for name in [&flag.short, &flag.long].into_iter().flatten() {
+            known_flags.add(name, flag.value_placeholder.is_some());
+        }
+    }
+    known_flags
+}
+
+fn empty_children() -> Value {
+    json!({ "COMMAND": {}, "FLAG": [], "USAGE": [], "HELP_TOPIC": [], "OTHER": [] })
+}
+
+/// Pairs `# comment` lines with the example command that follows them. A
+/// command line ending in a backslash continues on the next line.
+fn push_example_line(
+    examples: &mut Vec<CommandExample>,
+    pending_comment: &mut Option<String>,
+    line_contents: &str,
+) {
+    if let Some(comment) = line_contents.strip_prefix('#') {
+        let comment = comment.trim();
+        *pending_comment = Some(match pending_comment.take() {
+            Some(previous) => format!("{} {}", previous, comment),
+            None => comment.to_string(),
+        });
+        return;
+    }
+
+    if let Some(previous) = examples.last_mut()
+        && previous.command_line.ends_with('\\')
+    {
+        previous.command_line.push(' ');
+        previous.command_line.push_str(line_contents);
+        return;
+    }
+
+    let command_line = line_contents.strip_prefix("$ ").unwrap_or(line_contents);
+    examples.push(CommandExample {
+        comment: pending_comment.take(),
+        command_line: command_line.to_string(),
+    });
+}
+
+/// The previous parse of a command, when its help page is unchanged since
+fn unchanged_previous_node<'a>(
+    options: &'a CrawlOptions,
+    command_path: &str,
+    help_output: &Value,
+) -> Option<&'a Value> {
+    let previous_node = find_node(options.previous_structure.as_ref()?, command_path)?;
+    let help_page = help_output.get("stdout")?.as_str()?;
+    (node_help_hash(previous_node) == Some(help_page_hash(help_page))).then_some(previous_node)
+}
+
+/// Fills in a command node from its help page and what was parsed from it
+fn fill_command_node(
+    node: &mut Value,
+    help_output: Value,
+    parsed: &Value,
+    depth: usize,
+    command_path: &str,
+) {
+    node["children"] = parsed.get("children").cloned().unwrap_or_default();
+    node["outputs"] = json!({
+        "help_page": help_output,
+    });
+    if let Some(parsed_description) = parsed.get("description")
+        && !parsed_description.as_str().unwrap_or("").is_empty()
+    {
+        node["description"] = parsed_description.clone();
+    }
+    for key in ["aliases", "arguments", "examples"] {
+        node[key] = parsed.get(key).cloned().unwrap_or(json!([]));
+    }
+    node["depth"] = json!(depth);
+    node["command_path"] = json!(command_path);
+}
+
+/// Builds the node of a command listed on its parent's help page, running its
+/// `--help` and parsing it unless it has been crawled already or the depth
+/// limit is reached. Returns the command's name and node.
+fn crawl_child_command(
+    command: &str,
+    command_path: &str,
+    mut child_value: Value,
+    state: &mut CrawlState,
+    depth: usize,
+    options: &CrawlOptions,
+    warnings: &mut Vec<String>,
+) -> Option<(String, Value)> {
+    let cmd_name = child_value.get("name")?.as_str()?.to_string();
+    let parent_command = format!("{} {}", command, cmd_name);
+    let child_command_path = format!("{} {}", command_path, cmd_name);
+
+    if let Some(obj) = child_value.as_object_mut() {
+        obj.insert("children".to_string(), empty_children());
+    }
+
+    if state.visited.contains(&parent_command) {
+        child_value["depth"] = json!(depth + 1);
+        child_value["command_path"] = json!(child_command_path);
+        return Some((cmd_name, child_value));
+    }
+
+    if depth >= 5 {
+        warnings.push(format!(
+            "depth limit reached, `{}` not crawled",
+            parent_command
+        ));
+        return Some((cmd_name, child_value));
+    }
+
+    let (help_output, node_index) = crawl_help_page(
+        &parent_command,
+        &child_command_path,
+        options,
+        &mut state.report,
+    );
+    if help_output
+        .get("status")
+        .and_then(|s| s.as_i64())
+        .unwrap_or(-1)
+        == 0
+    {
+        let previous_node = unchanged_previous_node(options, &child_command_path, &help_output);
+        state.report.nodes[node_index].reused = previous_node.is_some();
+        let parsed_children = parse_help_output_dynamic(
+            &parent_command,
+            help_output["stdout"].as_str().unwrap_or_default(),
+            state,
+            depth + 1,
+            &child_command_path,
+            options,
+            previous_node,
+        );
+        add_parse_findings(&mut state.report, node_index, &parsed_children);
+        fill_command_node(
+            &mut child_value,
+            help_output,
+            &parsed_children,
+            depth + 1,
+            &child_command_path,
+        );
+    }
+    Some((cmd_name, child_value))
+}
+
+/// Takes a command's own parse from the previous structure instead of parsing
+/// its unchanged help page again. Its subcommands are still crawled, as their
+/// help pages may have changed.
+fn reuse_previous_node(
+    command: &str,
+    previous_node: &Value,
+    state: &mut CrawlState,
+    depth: usize,
+    command_path: &str,
+    options: &CrawlOptions,
+) -> Value {
+    let mut components = previous_node
+        .get("children")
+        .cloned()
+        .unwrap_or_else(empty_children);
+    components["COMMAND"] = json!({});
+    // Inheritance is linked again once the whole structure is crawled
+    if let Some(flags) = components.get_mut("FLAG").and_then(Value::as_array_mut) {
+        for flag in flags {
+            let inherited = flag["parent_header"]
+                .as_str()
+                .is_some_and(LineFlag::is_inherited_section);
+            flag["inherited"] = json!(inherited);
+            flag["persistent"] = json!(inherited);
+            flag["defined_in"] = Value::Null;
+        }
+    }
+
+    let mut warnings = Vec::new();
+    for (name, previous_child) in previous_node["children"]["COMMAND"]
+        .as_object()
+        .into_iter()
+        .flatten()
+    {
+        // External plugins are crawled through their own executable afterwards
+        if previous_child["external_plugin"].as_bool() == Some(true) {
+            continue;
+        }
+        let child_value = json!({
+            "name": name,
+            "description": previous_child["description"],
+            "parent_header": previous_child["parent_header"],
+            "parent": previous_child["parent"],
+            "children": [],
+        });
+        if let Some((name, node)) = crawl_child_command(
+            command,
+            command_path,
+            child_value,
+            state,
+            depth,
+            options,
+            &mut warnings,
+        ) {
+            components["COMMAND"][name] = node;
+        }
+    }
+
+    json!({
+        "description": previous_node["description"],
+        "aliases": previous_node.get("aliases").cloned().unwrap_or(json!([])),
+        "arguments": previous_node.get("arguments").cloned().unwrap_or(json!([])),
+        "examples": previous_node.get("examples").cloned().unwrap_or(json!([])),
+        "children": components,
+        "unclassified_lines": [],
+        "warnings": warnings
+    })
+}
+
 fn parse_help_output_dynamic(
-    _base_command: &str,
     command: &str,
     output: &str,
-    visited: &mut HashSet<String>,
+    state: &mut CrawlState,
     depth: usize,
     command_path: &str,
+    options: &CrawlOptions,
+    previous_node: Option<&Value>,
 ) -> Value {
     if depth > 5 {
         return json!({ "children": {} });
     }
 
-    if visited.contains(command) {
+    if state.visited.contains(command) {
         return json!({ "children": {} });
     }
-    visited.insert(command.to_string());
+    state.visited.insert(command.to_string());
+
+    if let Some(previous_node) = previous_node {
+        return reuse_previous_node(command, previous_node, state, depth, command_path, options);
+    }
 
-    let lines: Vec<String> = output.lines().map(|s| s.to_string()).collect();
+    let lines: Vec<String> = fold_continuation_lines(output);
+    let known_flags = known_flags(&lines);
     let mut description: Option<String> = None;
-    let mut components = json!({ "COMMAND": {}, "FLAG": [], "USAGE": [], "OTHER": [] });
+    let mut components = empty_children();
+    let mut aliases: Vec<String> = Vec::new();
+    let mut examples: Vec<CommandExample> = Vec::new();
+    let mut section_arguments: Vec<LineArgument> = Vec::new();
+    let mut unclassified_lines: Vec<String> = Vec::new();
+    let mut warnings: Vec<String> = Vec::new();
+    let mut pending_example_comment: Option<String> = None;
     let mut previous_section_header: Option<String> = None;
     let mut current_section_header: Option<String> = None;
 
@@ -268,126 +750,79 @@ fn parse_help_output_dynamic(
                 .or(Some(trimmed_line.to_string()));
             if current_section_header != previous_section_header {
                 previous_section_header = current_section_header.clone();
+                if let Some(header) = &current_section_header
+                    && trimmed_line.ends_with(':')
+                    && !is_known_section(header)
+                {
+                    warnings.push(format!("section header not recognised: '{}'", header));
+                }
             }
             continue;
         } else if let Some(section) = &current_section_header
             && (line.starts_with("  ") || line.starts_with("\t"))
             && let Some((line_type, child_json_str)) = handle_child_line(
-                command.split_whitespace().last().unwrap_or(""),
+                command_path.split_whitespace().last().unwrap_or(""),
                 section,
                 line,
+                &known_flags,
             )
         {
-            let mut child_value: Value = serde_json::from_str(&child_json_str).unwrap();
+            let child_value: Value = serde_json::from_str(&child_json_str).unwrap();
 
             match line_type {
                 ChildLineType::Command => {
-                    if let Some(cmd_name) = child_value.get("name").and_then(|v| v.as_str()) {
-                        let cmd_name = cmd_name.to_string();
-                        let parent_command = format!("{} {}", command, cmd_name);
-                        let child_command_path = format!("{} {}", command_path, cmd_name);
-
-                        if visited.contains(&parent_command) {
-                            if let Some(obj) = child_value.as_object_mut() {
-                                obj.insert(
-                                    "children".to_string(),
-                                    json!({
-                                        "COMMAND": {}, "FLAG": [], "USAGE": [], "OTHER": []
-                                    }),
-                                );
-                                obj.insert("depth".to_string(), json!(depth + 1));
-                                obj.insert("command_path".to_string(), json!(child_command_path));
-                            }
-
-                            if let Some(command_map) =
-                                components.get_mut("COMMAND").and_then(Value::as_object_mut)
-                            {
-                                command_map.insert(cmd_name.clone(), child_value);
-                            }
-                            continue;
-                        }
-
-                        if let Some(obj) = child_value.as_object_mut() {
-                            obj.insert(
-                                "children".to_string(),
-                                json!({
-                                    "COMMAND": {}, "FLAG": [], "USAGE": [], "OTHER": []
-                                }),
-                            );
-                        }
-
-                        if let Some(command_map) =
-                            components.get_mut("COMMAND").and_then(Value::as_object_mut)
-                        {
-                            command_map.insert(cmd_name.clone(), child_value);
-                        }
-
-                        if depth < 5 {
-                            let help_output =
-                                execute_full_command(&format!("{} --help", parent_command));
-
-                            if help_output
-                                .get("status")
-                                .and_then(|s| s.as_i64())
-                                .unwrap_or(-1)
-                                == 0
-                            {
-                                let parsed_children = parse_help_output_dynamic(
-                                    _base_command,
-                                    &parent_command,
-                                    help_output["stdout"].as_str().unwrap_or_default(),
-                                    visited,
-                                    depth + 1,
-                                    &child_command_path,
-                                );
-
-                                if let Some(command_map) =
-                                    components.get_mut("COMMAND").and_then(Value::as_object_mut)
-                                    && let Some(cmd_obj) = command_map.get_mut(&cmd_name)
-                                    && let Some(cmd_obj_map) = cmd_obj.as_object_mut()
-                                {
-                                    cmd_obj_map.insert(
-                                        "children".to_string(),
-                                        parsed_children
-                                            .get("children")
-                                            .cloned()
-                                            .unwrap_or_default(),
-                                    );
-                                    cmd_obj_map.insert(
-                                        "outputs".to_string(),
-                                        json!({
-                                            "help_page": help_output,
-                                        }),
-                                    );
-                                    if let Some(parsed_description) =
-                                        parsed_children.get("description")
-                                        && !parsed_description.as_str().unwrap_or("").is_empty()
-                                    {
-                                        cmd_obj_map.insert(
-                                            "description".to_string(),
-                                            parsed_description.clone(),
-                                        );
-                                    }
-                                    cmd_obj_map.insert("depth".to_string(), json!(depth + 1));
-                                    cmd_obj_map.insert(
-                                        "command_path".to_string(),
-                                        json!(child_command_path),
-                                    );
-                                }
-                            }
-                        }
+                    if let Some((name, node)) = crawl_child_command(
+                        command,
+                        command_path,
+                        child_value,
+                        state,
+                        depth,
+                        options,
+                        &mut warnings,
+                    ) {
+                        components["COMMAND"][name] = node;
                     }
                 }
                 ChildLineType::Flag => {
                     components["FLAG"].as_array_mut().unwrap().push(child_value);
                 }
                 ChildLineType::Usage => {
+                    let line_usage: LineUsage =
+                        serde_json::from_value(child_value.clone()).unwrap();
+                    for parse_error in &line_usage.parse_errors {
+                        warnings.push(format!(
+                            "usage '{}': {}",
+                            line_usage.usage_string, parse_error.message
+                        ));
+                    }
                     components["USAGE"]
                         .as_array_mut()
                         .unwrap()
                         .push(child_value);
                 }
+                ChildLineType::Aliases => {
+                    let line_aliases: LineAliases = serde_json::from_value(child_value).unwrap();
+                    aliases.extend(line_aliases.aliases);
+                }
+                ChildLineType::Example => {
+                    let line_example: LineExample = serde_json::from_value(child_value).unwrap();
+                    push_example_line(
+                        &mut examples,
+                        &mut pending_example_comment,
+                        &line_example.line_contents,
+                    );
+                }
+                ChildLineType::HelpTopic => {
+                    components["HELP_TOPIC"]
+                        .as_array_mut()
+                        .unwrap()
+                        .push(child_value);
+                }
+                ChildLineType::Argument => {
+                    section_arguments.push(serde_json::from_value(child_value).unwrap());
+                }
                 ChildLineType::Other => {
+                    unclassified_lines.push(trimmed_line.to_string());
                     components["OTHER"]
                         .as_array_mut()
                         .unwrap()
@@ -397,13 +832,280 @@ fn parse_help_output_dynamic(
         }
     }
 
+    let usages: Vec<Vec<UsageComponent>> = components["USAGE"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .filter_map(|usage| serde_json::from_value(usage["usage_components"].clone()).ok())
+        .collect();
+    let arguments = derive_arguments(&usages, &section_arguments);
+
     json!({
         "description": description.unwrap_or_default(),
-        "children": components
+        "aliases": aliases,
+        "arguments": arguments,
+        "examples": examples,
+        "children": components,
+        "unclassified_lines": unclassified_lines,
+        "warnings": warnings
     })
 }
 
-pub fn extract_cli_structure(base_command: &str, command_name: Option<String>) -> Value {
+pub fn flag_signature(flag: &Value) -> String {
+    let long = flag.get("long").and_then(|v| v.as_str()).unwrap_or("");
+    let short = flag.get("short").and_then(|v| v.as_str()).unwrap_or("");
+    if !long.is_empty() {
+        long.to_string()
+    } else {
+        short.to_string()
+    }
+}
+
+/// Links every inherited flag to the closest ancestor listing it as one of its
+/// own flags, and marks that definition as persistent. A flag under a global
+/// header that no ancestor defines is the command's own.
+fn resolve_inherited_flags(structure: &mut Value) {
+    let mut ancestors: Vec<(String, HashSet<String>)> = Vec::new();
+    let mut persistent_flags: HashSet<(String, String)> = HashSet::new();
+    link_inherited_flags(structure, &mut ancestors, &mut persistent_flags);
+    mark_persistent_flags(structure, &persistent_flags);
+}
+
+fn link_inherited_flags(
+    node: &mut Value,
+    ancestors: &mut Vec<(String, HashSet<String>)>,
+    persistent_flags: &mut HashSet<(String, String)>,
+) {
+    let command_path = node
+        .get("command_path")
+        .and_then(|v| v.as_str())
+        .unwrap_or_default()
+        .to_string();
+    let mut local_flags = HashSet::new();
+
+    if let Some(flags) = node
+        .get_mut("children")
+        .and_then(|c| c.get_mut("FLAG"))
+        .and_then(Value::as_array_mut)
+    {
+        for flag in flags {
+            let signature = flag_signature(flag);
+            if LineFlag::is_inherited(flag) {
+                let defined_in = ancestors
+                    .iter()
+                    .rev()
+                    .find(|(_, ancestor_flags)| ancestor_flags.contains(&signature))
+                    .map(|(ancestor_path, _)| ancestor_path.clone());
+                match &defined_in {
+                    Some(ancestor_path) => {
+                        persistent_flags.insert((ancestor_path.clone(), signature));
+                    }
+                    // Listed under a global header with no ancestor defining it,
+                    // like the root's own global flags, so it's defined here
+                    None => {
+                        flag["inherited"] = json!(false);
+                        local_flags.insert(signature);
+                    }
+                }
+                flag["defined_in"] = json!(defined_in);
+            } else {
+                local_flags.insert(signature);
+            }
+        }
+    }
+
+    ancestors.push((command_path, local_flags));
+    if let Some(command_map) = node
+        .get_mut("children")
+        .and_then(|c| c.get_mut("COMMAND"))
+        .and_then(Value::as_object_mut)
+    {
+        for child in command_map.values_mut() {
+            link_inherited_flags(child, ancestors, persistent_flags);
+        }
+    }
+    ancestors.pop();
+}
+
+fn mark_persistent_flags(node: &mut Value, persistent_flags: &HashSet<(String, String)>) {
+    let command_path = node
+        .get("command_path")
+        .and_then(|v| v.as_str())
+        .unwrap_or_default()
+        .to_string();
+
+    if let Some(flags) = node
+        .get_mut("children")
+        .and_then(|c| c.get_mut("FLAG"))
+        .and_then(Value::as_array_mut)
+    {
+        for flag in flags {
+            if persistent_flags.contains(&(command_path.clone(), flag_signature(flag))) {
+                flag["persistent"] = json!(true);
+            }
+        }
+    }
+
+    if let Some(command_map) = node
+        .get_mut("children")
+        .and_then(|c| c.get_mut("COMMAND"))
+        .and_then(Value::as_object_mut)
+    {
+        for child in command_map.values_mut() {
+            mark_persistent_flags(child, persistent_flags);
+        }
+    }
+}
+
+/// Adds external subcommands as commands of the root, crawling each through its
+/// own executable. A built-in command of the same name wins, as it does when
+/// the program dispatches.
+fn crawl_plugins(
+    structure: &mut Value,
+    plugins: &[Plugin],
+    state: &mut CrawlState,
+    options: &CrawlOptions,
+) {
+    let root_path = structure["command_path"]
+        .as_str()
+        .unwrap_or_default()
+        .to_string();
+    let root_name = root_path.split_whitespace().last().unwrap_or_default();
+
+    for plugin in plugins {
+        let Some(command_map) = structure["children"]
+            .get_mut("COMMAND")
+            .and_then(Value::as_object_mut)
+        else {
+            return;
+        };
+        if command_map.contains_key(&plugin.name) {
+            continue;
+        }
+
+        let command_path = format!("{} {}", root_path, plugin.name);
+        let mut node = json!({
+            "name": plugin.name,
+            "description": "",
+            "children": empty_children(),
+            "parent_header": PLUGIN_HEADER,
+            "parent": root_name,
+            "depth": 1,
+            "command_path": command_path,
+            "external_plugin": true,
+            "plugin_command": plugin.command,
+        });
+        let (help_output, node_index) =
+            crawl_help_page(&plugin.command, &command_path, options, &mut state.report);
+        if help_output["status"].as_i64() == Some(0) {
+            let previous_node = unchanged_previous_node(options, &command_path, &help_output);
+            state.report.nodes[node_index].reused = previous_node.is_some();
+            let parsed = parse_help_output_dynamic(
+                &plugin.command,
+                help_output["stdout"].as_str().unwrap_or_default(),
+                state,
+                1,
+                &command_path,
+                options,
+                previous_node,
+            );
+            add_parse_findings(&mut state.report, node_index, &parsed);
+            fill_command_node(&mut node, help_output, &parsed, 1, &command_path);
+        } else {
+            node["outputs"] = json!({ "help_page": help_output });
+        }
+
+        if let Some(command_map) = structure["children"]
+            .get_mut("COMMAND")
+            .and_then(Value::as_object_mut)
+        {
+            command_map.insert(plugin.name.clone(), node);
+        }
+    }
+}
+
+fn add_latency_profile(structure: &mut Value, options: &CrawlOptions) {
+    if let Some(runs) = options.profile_runs {
+        println!("Profiling startup latency ({} runs per command)", runs);
+        structure["metadata"]["latency"] = json!(profile_latency(structure, options, runs));
+    }
+}
+
+/// A command of a parse, as the crawl reached it
+pub struct WalkedCommand<'a> {
+    /// Taken from the tree, as commands whose help page failed don't record
+    /// theirs
+    pub command_path: String,
+    /// What is run for it: the command path, or the executable of an external
+    /// plugin
+    pub command: String,
+    pub node: &'a Value,
+    /// Index of the parent command in the walk, `None` for the root
+    pub parent: Option<usize>,
+}
+
+/// Every command of a parse in crawl order, parents before their children
+pub fn walk_commands(structure: &Value) -> Vec<WalkedCommand<'_>> {
+    fn collect<'a>(walked: WalkedCommand<'a>, commands: &mut Vec<WalkedCommand<'a>>) {
+        let index = commands.len();
+        let (node, command_path, command) = (
+            walked.node,
+            walked.command_path.clone(),
+            walked.command.clone(),
+        );
+        commands.push(walked);
+        for (name, child) in node["children"]["COMMAND"]
+            .as_object()
+            .into_iter()
+            .flatten()
+        {
+            let child_command = match child["plugin_command"].as_str() {
+                Some(plugin_command) => plugin_command.to_string(),
+                None => format!("{} {}", command, name),
+            };
+            let child = WalkedCommand {
+                command_path: format!("{} {}", command_path, name),
+                command: child_command,
+                node: child,
+                parent: Some(index),
+            };
+            collect(child, commands);
+        }
+    }
+
+    let root_path = structure["command_path"].as_str().unwrap_or_default();
+    let root = WalkedCommand {
+        command_path: root_path.to_string(),
+        command: root_path.to_string(),
+        node: structure,
+        parent: None,
+    };
+    let mut commands = Vec::new();
+    collect(root, &mut commands);
+    commands
+}
+
+/// Every command of a parse in crawl order, with what is run for it
+pub fn crawled_commands(structure: &Value) -> Vec<(String, String)> {
+    walk_commands(structure)
+        .into_iter()
+        .map(|walked| (walked.command_path, walked.command))
+        .collect()
+}
+
+/// Every command node of a parse in crawl order, with its command path
+pub fn command_nodes(structure: &Value) -> Vec<(String, &Value)> {
+    walk_commands(structure)
+        .into_iter()
+        .map(|walked| (walked.command_path, walked.node))
+        .collect()
+}
+
+pub fn extract_cli_structure(
+    base_command: &str,
+    command_name: Option<String>,
+    options: &CrawlOptions,
+) -> Value {
     let current_command_name = match command_name {
         Some(name) => format!("{} {}", base_command, name),
         None => base_command.to_string(),
@@ -414,31 +1116,67 @@ pub fn extract_cli_structure(base_command: &str, command_name: Option<String>) -
         "description": "",
         "children": {},
         "outputs": {},
-        "version": get_program_version(base_command),
+        "version": get_program_version(base_command, options),
         "depth": 0,
-        "command_path": current_command_name
+        "command_path": current_command_name,
+        "metadata": {
+            "environment": options.environment.to_metadata(),
+        }
     });
+    let help_source = options.execution_mode.help_source();
+    if !help_source.is_null() {
+        structure["metadata"]["help_source"] = help_source;
+    }
 
-    let help_output = execute_full_command(&format!("{} --help", current_command_name));
+    let mut state = CrawlState::default();
+    let (help_output, root_index) = crawl_help_page(
+        &current_command_name,
+        &current_command_name,
+        options,
+        &mut state.report,
+    );
 
     structure["outputs"] = json!({
         "help_page": help_output,
     });
 
-    let mut visited = HashSet::new();
+    let previous_root = unchanged_previous_node(
+        options,
+        &current_command_name,
+        &structure["outputs"]["help_page"],
+    );
+    state.report.nodes[root_index].reused = previous_root.is_some();
     let parsed = parse_help_output_dynamic(
-        base_command,
         &current_command_name,
         structure["outputs"]["help_page"]["stdout"]
             .as_str()
             .unwrap_or_default(),
-        &mut visited,
+        &mut state,
         0,
         &current_command_name,
+        options,
+        previous_root,
     );
 
     structure["description"] = parsed.get("description").cloned().unwrap_or(json!(""));
+    structure["aliases"] = parsed.get("aliases").cloned().unwrap_or(json!([]));
+    structure["arguments"] = parsed.get("arguments").cloned().unwrap_or(json!([]));
+    structure["examples"] = parsed.get("examples").cloned().unwrap_or(json!([]));
     structure["children"] = parsed.get("children").cloned().unwrap_or(json!({}));
+    add_parse_findings(&mut state.report, root_index, &parsed);
+    if let Some(plugin_dirs) = &options.plugin_dirs {
+        crawl_plugins(
+            &mut structure,
+            &discover_plugins(base_command, plugin_dirs),
+            &mut state,
+            options,
+        );
+    }
+    structure["metadata"]["crawl_report"] = json!(state.report);
+
+    resolve_inherited_flags(&mut structure);
+    score_structure(&mut structure);
+    add_latency_profile(&mut structure, options);
 
     structure
 }
@@ -3,6 +3,10 @@ use std::collections::{HashMap, HashSet};
 use std::fs;
 use std::path::Path;
 
+use crate::latency::{REGRESSION_FACTOR, is_slowdown, latency_by_command};
+use crate::models::{CommandArgument, LineFlag, Severity};
+use crate::variants::filter_by_variant;
+
 #[derive(Debug, Clone)]
 pub enum ChangeType {
     CommandAdded {
@@ -33,9 +37,166 @@ pub enum ChangeType {
         old_type: Option<String>,
         new_type: Option<String>,
     },
+    AliasAdded {
+        command: String,
+        alias: String,
+    },
+    AliasRemoved {
+        command: String,
+        alias: String,
+    },
+    ExampleAdded {
+        command: String,
+        example: String,
+    },
+    ExampleRemoved {
+        command: String,
+        example: String,
+    },
+    HelpTopicAdded {
+        parent: String,
+        topic: String,
+    },
+    HelpTopicRemoved {
+        parent: String,
+        topic: String,
+    },
+    ArgumentAdded {
+        command: String,
+        argument: String,
+    },
+    ArgumentRemoved {
+        command: String,
+        argument: String,
+    },
+    ArgumentChanged {
+        command: String,
+        argument: String,
+        field: String,
+        old_value: String,
+        new_value: String,
+    },
+    LatencyRegressed {
+        command: String,
+        old_ms: f64,
+        new_ms: f64,
+    },
 }
 
 impl ChangeType {
+    /// The rule SARIF and JUnit reports file the change under
+    pub fn rule_id(&self) -> &'static str {
+        match self {
+            ChangeType::CommandAdded { .. } => "command-added",
+            ChangeType::CommandRemoved { .. } => "command-removed",
+            ChangeType::FlagAdded { .. } => "flag-added",
+            ChangeType::FlagRemoved { .. } => "flag-removed",
+            ChangeType::FlagDescriptionChanged { .. } => "flag-description-changed",
+            ChangeType::FlagDataTypeChanged { .. } => "flag-data-type-changed",
+            ChangeType::AliasAdded { .. } => "alias-added",
+            ChangeType::AliasRemoved { .. } => "alias-removed",
+            ChangeType::ExampleAdded { .. } => "example-added",
+            ChangeType::ExampleRemoved { .. } => "example-removed",
+            ChangeType::HelpTopicAdded { .. } => "help-topic-added",
+            ChangeType::HelpTopicRemoved { .. } => "help-topic-removed",
+            ChangeType::ArgumentAdded { .. } => "argument-added",
+            ChangeType::ArgumentRemoved { .. } => "argument-removed",
+            ChangeType::ArgumentChanged { .. } => "argument-changed",
+            ChangeType::LatencyRegressed { .. } => "latency-regressed",
+        }
+    }
+
+    /// Changes that break existing scripts are errors, ones that might or
+    /// that users notice are warnings, and additions are info
+    pub fn severity(&self) -> Severity {
+        match self {
+            ChangeType::CommandRemoved { .. }
+            | ChangeType::FlagRemoved { .. }
+            | ChangeType::FlagDataTypeChanged { .. }
+            | ChangeType::AliasRemoved { .. }
+            | ChangeType::ArgumentRemoved { .. }
+            | ChangeType::ArgumentChanged { .. } => Severity::Error,
+            ChangeType::ArgumentAdded { .. }
+            | ChangeType::ExampleRemoved { .. }
+            | ChangeType::HelpTopicRemoved { .. }
+            | ChangeType::LatencyRegressed { .. } => Severity::Warning,
+            ChangeType::CommandAdded { .. }
+            | ChangeType::FlagAdded { .. }
+            | ChangeType::FlagDescriptionChanged { .. }
+            | ChangeType::AliasAdded { .. }
+            | ChangeType::ExampleAdded { .. }
+            | ChangeType::HelpTopicAdded { .. } => Severity::Info,
+        }
+    }
+
+    /// Whether the change is about something only the old version has
+    pub fn is_removal(&self) -> bool {
+        matches!(
+            self,
+            ChangeType::CommandRemoved { .. }
+                | ChangeType::FlagRemoved { .. }
+                | ChangeType::AliasRemoved { .. }
+                | ChangeType::ExampleRemoved { .. }
+                | ChangeType::HelpTopicRemoved { .. }
+                | ChangeType::ArgumentRemoved { .. }
+        )
+    }
+
+    /// The full command path of the changed command. Changes are recorded
+    /// relative to the root, except for the root's own flags and latency.
+    pub fn command_path(&self, root_name: &str) -> String {
+        let relative_path = match self {
+            ChangeType::CommandAdded { parent, command }
+            | ChangeType::CommandRemoved { parent, command } => {
+                format!("{} {}", parent, command)
+            }
+            ChangeType::HelpTopicAdded { parent, .. }
+            | ChangeType::HelpTopicRemoved { parent, .. } => parent.clone(),
+            ChangeType::FlagAdded { command, .. }
+            | ChangeType::FlagRemoved { command, .. }
+            | ChangeType::FlagDescriptionChanged { command, .. }
+            | ChangeType::FlagDataTypeChanged { command, .. }
+            | ChangeType::AliasAdded { command, .. }
+            | ChangeType::AliasRemoved { command, .. }
+            | ChangeType::ExampleAdded { command, .. }
+            | ChangeType::ExampleRemoved { command, .. }
+            | ChangeType::ArgumentAdded { command, .. }
+            | ChangeType::ArgumentRemoved { command, .. }
+            | ChangeType::ArgumentChanged { command, .. }
+            | ChangeType::LatencyRegressed { command, .. }
+                if command == root_name || command.starts_with(&format!("{} ", root_name)) =>
+            {
+                return command.clone();
+            }
+            ChangeType::FlagAdded { command, .. }
+            | ChangeType::FlagRemoved { command, .. }
+            | ChangeType::FlagDescriptionChanged { command, .. }
+            | ChangeType::FlagDataTypeChanged { command, .. }
+            | ChangeType::AliasAdded { command, .. }
+            | ChangeType::AliasRemoved { command, .. }
+            | ChangeType::ExampleAdded { command, .. }
+            | ChangeType::ExampleRemoved { command, .. }
+            | ChangeType::ArgumentAdded { command, .. }
+            | ChangeType::ArgumentRemoved { command, .. }
+            | ChangeType::ArgumentChanged { command, .. }
+            | ChangeType::LatencyRegressed { command, .. } => command.clone(),
+        };
+        format!("{} {}", root_name, relative_path.trim())
+            .trim()
+            .to_string()
+    }
+
+    /// The changed flag, as `-s/--long` or either form alone
+    pub fn flag(&self) -> Option<&str> {
+        match self {
+            ChangeType::FlagAdded { flag, .. }
+            | ChangeType::FlagRemoved { flag, .. }
+            | ChangeType::FlagDescriptionChanged { flag, .. }
+            | ChangeType::FlagDataTypeChanged { flag, .. } => Some(flag),
+            _ => None,
+        }
+    }
+
     pub fn format(&self) -> String {
         match self {
             ChangeType::CommandAdded { parent, command } => {
@@ -82,6 +243,60 @@ impl ChangeType {
                     flag, command, old_str, new_str
                 )
             }
+            ChangeType::AliasAdded { command, alias } => {
+                format!("+ Added alias: {} (command: {})", alias, command)
+            }
+            ChangeType::AliasRemoved { command, alias } => {
+                format!("- Removed alias: {} (command: {})", alias, command)
+            }
+            ChangeType::ExampleAdded { command, example } => {
+                format!("+ Added example: {} (command: {})", example, command)
+            }
+            ChangeType::ExampleRemoved { command, example } => {
+                format!("- Removed example: {} (command: {})", example, command)
+            }
+            ChangeType::HelpTopicAdded { parent, topic } => {
+                if parent.is_empty() {
+                    format!("+ Added help topic: {}", topic)
+                } else {
+                    format!("+ Added help topic: {} (to {})", topic, parent)
+                }
+            }
+            ChangeType::HelpTopicRemoved { parent, topic } => {
+                if parent.is_empty() {
+                    format!("- Removed help topic: {}", topic)
+                } else {
+                    format!("- Removed help topic: {} (from {})", topic, parent)
+                }
+            }
+            ChangeType::ArgumentAdded { command, argument } => {
+                format!("+ Added argument: {} (command: {})", argument, command)
+            }
+            ChangeType::ArgumentRemoved { command, argument } => {
+                format!("- Removed argument: {} (command: {})", argument, command)
+            }
+            ChangeType::ArgumentChanged {
+                command,
+                argument,
+                field,
+                old_value,
+                new_value,
+            } => {
+                format!(
+                    "~ Modified argument: {} (command: {})\n    {} changed: {} -> {}",
+                    argument, command, field, old_value, new_value
+                )
+            }
+            ChangeType::LatencyRegressed {
+                command,
+                old_ms,
+                new_ms,
+            } => {
+                format!(
+                    "~ Slower startup: {} (median {:.1} ms -> {:.1} ms)",
+                    command, old_ms, new_ms
+                )
+            }
         }
     }
 }
@@ -89,15 +304,28 @@ impl ChangeType {
 pub fn compare_json_structures(
     from_path: &Path,
     to_path: &Path,
+    variant: Option<&str>,
 ) -> Result<Vec<ChangeType>, Box<dyn std::error::Error>> {
     let from_content = fs::read_to_string(from_path)?;
     let to_content = fs::read_to_string(to_path)?;
 
-    let from_json: Value = serde_json::from_str(&from_content)?;
-    let to_json: Value = serde_json::from_str(&to_content)?;
+    let mut from_json: Value = serde_json::from_str(&from_content)?;
+    let mut to_json: Value = serde_json::from_str(&to_content)?;
+
+    if let Some(variant) = variant {
+        filter_by_variant(&mut from_json, variant);
+        filter_by_variant(&mut to_json, variant);
+    }
 
     let mut changes = Vec::new();
+
+    // Persistent flags are defined on the root, and only listed as inherited below it
+    let root_name = to_json.get("name").and_then(|v| v.as_str()).unwrap_or("");
+    compare_flags_json(&from_json, &to_json, root_name, &mut changes);
+    compare_arguments_json(&from_json, &to_json, root_name, &mut changes);
+
     compare_commands_json(&from_json, &to_json, "", &mut changes);
+    compare_latency_json(&from_json, &to_json, &mut changes);
 
     Ok(changes)
 }
@@ -127,6 +355,8 @@ fn compare_commands_json(
         .map(|cmds| cmds.keys().cloned().collect())
         .unwrap_or_default();
 
+    compare_help_topics_json(from, to, parent_path, changes);
+
     // Find added commands
     for command in to_set.difference(&from_set) {
         changes.push(ChangeType::CommandAdded {
@@ -158,6 +388,12 @@ fn compare_commands_json(
                 // Compare flags for this command
                 compare_flags_json(from_cmd, to_cmd, &current_path, changes);
 
+                compare_arguments_json(from_cmd, to_cmd, &current_path, changes);
+
+                // Compare aliases and examples for this command
+                compare_aliases_json(from_cmd, to_cmd, &current_path, changes);
+                compare_examples_json(from_cmd, to_cmd, &current_path, changes);
+
                 // Recursively compare subcommands
                 compare_commands_json(from_cmd, to_cmd, &current_path, changes);
             }
@@ -165,6 +401,26 @@ fn compare_commands_json(
     }
 }
 
+/// Commands whose `--help` got much slower, when both parses were profiled
+fn compare_latency_json(from: &Value, to: &Value, changes: &mut Vec<ChangeType>) {
+    let from_latency = latency_by_command(from);
+    let to_latency = latency_by_command(to);
+    let mut command_paths: Vec<&String> = to_latency.keys().collect();
+    command_paths.sort();
+    for command_path in command_paths {
+        if let Some(&old_ms) = from_latency.get(command_path)
+            && let Some(&new_ms) = to_latency.get(command_path)
+            && is_slowdown(new_ms, old_ms, REGRESSION_FACTOR)
+        {
+            changes.push(ChangeType::LatencyRegressed {
+                command: command_path.clone(),
+                old_ms,
+                new_ms,
+            });
+        }
+    }
+}
+
 fn compare_flags_json(from: &Value, to: &Value, command_path: &str, changes: &mut Vec<ChangeType>) {
     let from_flags = extract_flags_from_json(from);
     let to_flags = extract_flags_from_json(to);
@@ -224,15 +480,16 @@ fn compare_flags_json(from: &Value, to: &Value, command_path: &str, changes: &mu
                 });
             }
 
-            // Compare data types
-            let from_type = from_flag
-                .get("data_type")
-                .and_then(|v| v.as_str())
-                .map(|s| s.to_string());
-            let to_type = to_flag
-                .get("data_type")
-                .and_then(|v| v.as_str())
-                .map(|s| s.to_string());
+            // Compare data types: Cobra's own type where there is one, as
+            // placeholders are renamed freely
+            let flag_type = |flag: &Value| {
+                flag.get("pflag_type")
+                    .and_then(|v| v.as_str())
+                    .or(flag.get("data_type").and_then(|v| v.as_str()))
+                    .map(|s| s.to_string())
+            };
+            let from_type = flag_type(from_flag);
+            let to_type = flag_type(to_flag);
 
             if from_type != to_type {
                 changes.push(ChangeType::FlagDataTypeChanged {
@@ -246,12 +503,181 @@ fn compare_flags_json(from: &Value, to: &Value, command_path: &str, changes: &mu
     }
 }
 
+fn compare_arguments_json(
+    from: &Value,
+    to: &Value,
+    command_path: &str,
+    changes: &mut Vec<ChangeType>,
+) {
+    let from_arguments = extract_arguments_from_json(from);
+    let to_arguments = extract_arguments_from_json(to);
+
+    for argument in to_arguments
+        .iter()
+        .filter(|a| !from_arguments.iter().any(|f| f.name == a.name))
+    {
+        changes.push(ChangeType::ArgumentAdded {
+            command: command_path.to_string(),
+            argument: argument.name.clone(),
+        });
+    }
+
+    for from_argument in &from_arguments {
+        let Some(to_argument) = to_arguments.iter().find(|a| a.name == from_argument.name) else {
+            changes.push(ChangeType::ArgumentRemoved {
+                command: command_path.to_string(),
+                argument: from_argument.name.clone(),
+            });
+            continue;
+        };
+
+        let fields = [
+            (
+                "Position",
+                from_argument.position.to_string(),
+                to_argument.position.to_string(),
+            ),
+            (
+                "Required",
+                from_argument.required.to_string(),
+                to_argument.required.to_string(),
+            ),
+            (
+                "Variadic",
+                from_argument.variadic.to_string(),
+                to_argument.variadic.to_string(),
+            ),
+        ];
+        for (field, old_value, new_value) in fields {
+            if old_value != new_value {
+                changes.push(ChangeType::ArgumentChanged {
+                    command: command_path.to_string(),
+                    argument: from_argument.name.clone(),
+                    field: field.to_string(),
+                    old_value,
+                    new_value,
+                });
+            }
+        }
+    }
+}
+
+fn compare_aliases_json(
+    from: &Value,
+    to: &Value,
+    command_path: &str,
+    changes: &mut Vec<ChangeType>,
+) {
+    let from_aliases = extract_string_set(from.get("aliases"), None);
+    let to_aliases = extract_string_set(to.get("aliases"), None);
+
+    for alias in to_aliases.difference(&from_aliases) {
+        changes.push(ChangeType::AliasAdded {
+            command: command_path.to_string(),
+            alias: alias.clone(),
+        });
+    }
+
+    for alias in from_aliases.difference(&to_aliases) {
+        changes.push(ChangeType::AliasRemoved {
+            command: command_path.to_string(),
+            alias: alias.clone(),
+        });
+    }
+}
+
+fn compare_examples_json(
+    from: &Value,
+    to: &Value,
+    command_path: &str,
+    changes: &mut Vec<ChangeType>,
+) {
+    let from_examples = extract_string_set(from.get("examples"), Some("command_line"));
+    let to_examples = extract_string_set(to.get("examples"), Some("command_line"));
+
+    for example in to_examples.difference(&from_examples) {
+        changes.push(ChangeType::ExampleAdded {
+            command: command_path.to_string(),
+            example: example.clone(),
+        });
+    }
+
+    for example in from_examples.difference(&to_examples) {
+        changes.push(ChangeType::ExampleRemoved {
+            command: command_path.to_string(),
+            example: example.clone(),
+        });
+    }
+}
+
+fn compare_help_topics_json(
+    from: &Value,
+    to: &Value,
+    parent_path: &str,
+    changes: &mut Vec<ChangeType>,
+) {
+    let from_topics = extract_string_set(
+        from.get("children").and_then(|c| c.get("HELP_TOPIC")),
+        Some("name"),
+    );
+    let to_topics = extract_string_set(
+        to.get("children").and_then(|c| c.get("HELP_TOPIC")),
+        Some("name"),
+    );
+
+    for topic in to_topics.difference(&from_topics) {
+        changes.push(ChangeType::HelpTopicAdded {
+            parent: parent_path.to_string(),
+            topic: topic.clone(),
+        });
+    }
+
+    for topic in from_topics.difference(&to_topics) {
+        changes.push(ChangeType::HelpTopicRemoved {
+            parent: parent_path.to_string(),
+            topic: topic.clone(),
+        });
+    }
+}
+
+/// Collects the strings of a JSON array, or a string field of each object in it
+fn extract_string_set(array: Option<&Value>, field: Option<&str>) -> HashSet<String> {
+    array
+        .and_then(|a| a.as_array())
+        .map(|items| {
+            items
+                .iter()
+                .filter_map(|item| match field {
+                    Some(field) => item.get(field).and_then(|v| v.as_str()),
+                    None => item.as_str(),
+                })
+                .map(|s| s.to_string())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn extract_arguments_from_json(structure: &Value) -> Vec<CommandArgument> {
+    structure
+        .get("arguments")
+        .cloned()
+        .and_then(|arguments| serde_json::from_value(arguments).ok())
+        .unwrap_or_default()
+}
+
+/// Flags defined by the command itself. Inherited flags are compared once, on
+/// the command that defines them.
 fn extract_flags_from_json(structure: &Value) -> Vec<&Value> {
     structure
         .get("children")
         .and_then(|c| c.get("FLAG"))
         .and_then(|f| f.as_array())
-        .map(|flags| flags.iter().collect())
+        .map(|flags| {
+            flags
+                .iter()
+                .filter(|flag| !LineFlag::is_inherited(flag))
+                .collect()
+        })
         .unwrap_or_default()
 }
 
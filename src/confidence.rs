@@ -0,0 +1,280 @@
+use serde_json::{Value, json};
+use std::collections::HashSet;
+
+use crate::argument_parser::argument_name;
+use crate::models::{ComponentType, UsageComponent};
+
+pub const HEADER_MATCHED: &str = "header_matched";
+pub const COLUMN_ALIGNED: &str = "column_aligned";
+pub const HELP_PAGE_PARSED: &str = "help_page_parsed";
+pub const HELP_PAGE_CLEAN: &str = "help_page_clean";
+pub const IN_USAGE: &str = "in_usage";
+pub const DESCRIPTION_PRESENT: &str = "description_present";
+pub const PLUGIN_DISCOVERED: &str = "plugin_discovered";
+
+/// How much each piece of evidence adds to a node's confidence. Only what
+/// tells entries apart is weighted: every flag line starts like a flag and
+/// every subcommand comes from a command section, so neither counts.
+/// `plugin_discovered` only says where an external subcommand came from.
+const EVIDENCE_WEIGHTS: [(&str, f64); 7] = [
+    (HEADER_MATCHED, 0.3),
+    (COLUMN_ALIGNED, 0.2),
+    (HELP_PAGE_PARSED, 0.5),
+    (HELP_PAGE_CLEAN, 0.4),
+    (IN_USAGE, 0.4),
+    (DESCRIPTION_PRESENT, 0.1),
+    (PLUGIN_DISCOVERED, 0.0),
+];
+
+/// Sums the weights of the evidence, capped at 1
+pub fn confidence_score(evidence: &[String]) -> f64 {
+    let score: f64 = EVIDENCE_WEIGHTS
+        .iter()
+        .filter(|(kind, _)| evidence.iter().any(|e| e == kind))
+        .map(|(_, weight)| weight)
+        .sum();
+    (score.min(1.0) * 100.0).round() / 100.0
+}
+
+fn has_description(node: &Value) -> bool {
+    node.get("description")
+        .and_then(|d| d.as_str())
+        .is_some_and(|d| !d.trim().is_empty())
+}
+
+fn set_confidence(node: &mut Value, evidence: Vec<String>) {
+    node["confidence"] = json!(confidence_score(&evidence));
+    node["evidence"] = json!(evidence);
+}
+
+/// Flag names and words of every usage line of a command
+fn usage_names(node: &Value) -> HashSet<String> {
+    fn collect(components: &[UsageComponent], names: &mut HashSet<String>) {
+        for component in components {
+            match component.component_type {
+                ComponentType::Flag => {
+                    names.insert(component.name.clone());
+                }
+                ComponentType::Group | ComponentType::AlternativeGroup => {
+                    collect(&component.children, names);
+                    collect(&component.alternatives, names);
+                }
+                _ => {
+                    names.insert(argument_name(&component.name));
+                }
+            }
+        }
+    }
+
+    let mut names = HashSet::new();
+    let usages = node
+        .get("children")
+        .and_then(|c| c.get("USAGE"))
+        .and_then(Value::as_array)
+        .cloned()
+        .unwrap_or_default();
+    for usage in usages {
+        // The usage line starts with the command path, which the components leave out
+        if let Some(usage_string) = usage.get("usage_string").and_then(|u| u.as_str()) {
+            names.extend(usage_string.split_whitespace().map(String::from));
+        }
+        if let Ok(components) =
+            serde_json::from_value::<Vec<UsageComponent>>(usage["usage_components"].clone())
+        {
+            collect(&components, &mut names);
+        }
+    }
+    names
+}
+
+/// Evidence recorded when the entry was parsed
+fn recorded_evidence(entry: &Value) -> Vec<String> {
+    entry
+        .get("evidence")
+        .and_then(Value::as_array)
+        .map(|e| {
+            e.iter()
+                .filter_map(|v| v.as_str().map(String::from))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn score_flag(flag: &mut Value, usage_names: &HashSet<String>) {
+    // Whether the description sat in its own column is recorded when the
+    // line is parsed
+    let mut evidence = Vec::new();
+    if recorded_evidence(flag).iter().any(|e| e == COLUMN_ALIGNED) {
+        evidence.push(COLUMN_ALIGNED.to_string());
+    }
+    let header = flag
+        .get("parent_header")
+        .and_then(|h| h.as_str())
+        .unwrap_or_default()
+        .to_lowercase();
+    if header.contains("flag") || header.contains("option") {
+        evidence.push(HEADER_MATCHED.to_string());
+    }
+    if ["short", "long"]
+        .iter()
+        .filter_map(|key| flag.get(key).and_then(|v| v.as_str()))
+        .any(|name| usage_names.contains(name))
+    {
+        evidence.push(IN_USAGE.to_string());
+    }
+    if has_description(flag) {
+        evidence.push(DESCRIPTION_PRESENT.to_string());
+    }
+    set_confidence(flag, evidence);
+}
+
+fn score_argument(argument: &mut Value) {
+    // Where the argument was found is recorded when it is derived
+    let mut evidence = recorded_evidence(argument);
+    if has_description(argument) && !evidence.iter().any(|e| e == DESCRIPTION_PRESENT) {
+        evidence.push(DESCRIPTION_PRESENT.to_string());
+    }
+    set_confidence(argument, evidence);
+}
+
+fn score_command(command: &mut Value, unclean_pages: &HashSet<String>) {
+    // Subcommands are only picked up from command sections, so what tells
+    // them apart is whether their own help page could be read
+    let mut evidence = Vec::new();
+    if command.get("external_plugin").and_then(Value::as_bool) == Some(true) {
+        evidence.push(PLUGIN_DISCOVERED.to_string());
+    }
+    if command
+        .get("outputs")
+        .and_then(|o| o.get("help_page"))
+        .and_then(|h| h.get("status"))
+        .and_then(|s| s.as_i64())
+        == Some(0)
+    {
+        evidence.push(HELP_PAGE_PARSED.to_string());
+        let command_path = command
+            .get("command_path")
+            .and_then(|p| p.as_str())
+            .unwrap_or_default();
+        if !unclean_pages.contains(command_path) {
+            evidence.push(HELP_PAGE_CLEAN.to_string());
+        }
+    }
+    if has_description(command) {
+        evidence.push(DESCRIPTION_PRESENT.to_string());
+    }
+    set_confidence(command, evidence);
+}
+
+/// Commands whose help page had lines or sections the parser didn't recognise,
+/// from the crawl report
+fn unclean_pages(structure: &Value) -> HashSet<String> {
+    structure["metadata"]["crawl_report"]["nodes"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter(|node| {
+            ["unclassified_lines", "warnings"]
+                .iter()
+                .any(|key| node[key].as_array().is_some_and(|lines| !lines.is_empty()))
+        })
+        .filter_map(|node| node["command_path"].as_str().map(String::from))
+        .collect()
+}
+
+/// Scores the flags, arguments and subcommands of a parse. The root itself is
+/// the program that was asked for, so it isn't scored.
+pub fn score_structure(structure: &mut Value) {
+    let unclean_pages = unclean_pages(structure);
+    score_node(structure, &unclean_pages);
+}
+
+fn score_node(node: &mut Value, unclean_pages: &HashSet<String>) {
+    let names = usage_names(node);
+    if let Some(flags) = node
+        .get_mut("children")
+        .and_then(|c| c.get_mut("FLAG"))
+        .and_then(Value::as_array_mut)
+    {
+        for flag in flags {
+            score_flag(flag, &names);
+        }
+    }
+    if let Some(arguments) = node.get_mut("arguments").and_then(Value::as_array_mut) {
+        for argument in arguments {
+            score_argument(argument);
+        }
+    }
+    if let Some(command_map) = node
+        .get_mut("children")
+        .and_then(|c| c.get_mut("COMMAND"))
+        .and_then(Value::as_object_mut)
+    {
+        for command in command_map.values_mut() {
+            score_command(command, unclean_pages);
+            score_node(command, unclean_pages);
+        }
+    }
+}
+
+/// Nodes parsed before confidence scores were added are treated as certain
+fn is_confident(node: &Value, min_confidence: f64) -> bool {
+    node.get("confidence")
+        .and_then(|c| c.as_f64())
+        .is_none_or(|confidence| confidence >= min_confidence)
+}
+
+/// Drops the commands, flags and arguments scoring below `min_confidence`. A
+/// dropped command takes its subcommands with it.
+pub fn filter_by_confidence(node: &mut Value, min_confidence: f64) {
+    if let Some(flags) = node
+        .get_mut("children")
+        .and_then(|c| c.get_mut("FLAG"))
+        .and_then(Value::as_array_mut)
+    {
+        flags.retain(|flag| is_confident(flag, min_confidence));
+    }
+    if let Some(arguments) = node.get_mut("arguments").and_then(Value::as_array_mut) {
+        arguments.retain(|argument| is_confident(argument, min_confidence));
+    }
+    if let Some(command_map) = node
+        .get_mut("children")
+        .and_then(|c| c.get_mut("COMMAND"))
+        .and_then(Value::as_object_mut)
+    {
+        command_map.retain(|_, command| is_confident(command, min_confidence));
+        for command in command_map.values_mut() {
+            filter_by_confidence(command, min_confidence);
+        }
+    }
+}
+
+/// Marks the commands, flags and arguments scoring below `min_confidence` with
+/// `"low_confidence": true`, for the web interface to highlight
+pub fn mark_low_confidence(node: &mut Value, min_confidence: f64) {
+    let mark = |entry: &mut Value| {
+        if !is_confident(entry, min_confidence) {
+            entry["low_confidence"] = json!(true);
+        }
+    };
+    if let Some(flags) = node
+        .get_mut("children")
+        .and_then(|c| c.get_mut("FLAG"))
+        .and_then(Value::as_array_mut)
+    {
+        flags.iter_mut().for_each(mark);
+    }
+    if let Some(arguments) = node.get_mut("arguments").and_then(Value::as_array_mut) {
+        arguments.iter_mut().for_each(mark);
+    }
+    if let Some(command_map) = node
+        .get_mut("children")
+        .and_then(|c| c.get_mut("COMMAND"))
+        .and_then(Value::as_object_mut)
+    {
+        for command in command_map.values_mut() {
+            mark(command);
+            mark_low_confidence(command, min_confidence);
+        }
+    }
+}
@@ -0,0 +1,141 @@
+use std::sync::LazyLock;
+
+use regex::Regex;
+use serde_json::Value;
+
+use crate::cli_parser::{execute_full_command, walk_commands};
+use crate::models::{CrawlOptions, ErrorProbe, ErrorProbeReport, ErrorProbeResult};
+
+/// A flag no program defines
+pub const UNKNOWN_FLAG: &str = "--clint-probe-unknown-flag";
+/// A positional argument no command expects
+pub const EXTRA_ARGUMENT: &str = "clint-probe-extra-argument";
+/// Stands in for the required arguments when probing with an extra one
+const ARGUMENT_PLACEHOLDER: &str = "clint-probe-value";
+
+/// Phrases of error messages that say what was expected or what to do
+/// instead. Whole phrases, so that "invalid" or a bare "expected" don't count.
+static FIX_HINT_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(concat!(
+        r"\bdid you mean\b",
+        r"|\busage:",
+        r"|\bexpected (one of|an? |at least|at most|exactly|\d)",
+        r"|\b(is|are) required\b",
+        r"|\brequired (flag|option|argument|arg|parameter|value)s?\b",
+        r"|\b(requires|accepts) (at least |at most |exactly |between )?(an? |\d+ )",
+        r"|\bmissing (required )?(argument|arg|operand|parameter|value)s?\b",
+        r"|\bpossible values\b",
+        r"|\bvalid (values|options|choices|arguments|commands)\b",
+    ))
+    .unwrap()
+});
+
+/// Whether the output points at `-h`, `--help` or `<prog> help`, as words of
+/// their own rather than inside another flag such as `-hostname`
+fn suggests_help(output: &str, program_name: &str) -> bool {
+    let mut words = output
+        .split(|c: char| c.is_whitespace() || "'\"`()[]<>,;:".contains(c))
+        .map(|word| word.trim_end_matches('.'))
+        .filter(|word| !word.is_empty())
+        .peekable();
+    let program_name = program_name.to_lowercase();
+    while let Some(word) = words.next() {
+        if word == "-h" || word == "--help" {
+            return true;
+        }
+        if word == program_name && words.peek() == Some(&"help") {
+            return true;
+        }
+    }
+    false
+}
+
+fn suggests_fix(output: &str) -> bool {
+    FIX_HINT_RE.is_match(output)
+}
+
+/// The wrong invocations tried on a command. Commands without required
+/// arguments can't miss one, and a variadic argument takes any extra one.
+///
+/// An unknown flag is rejected before the command does anything, but a
+/// command run with the wrong arguments may go ahead with them, so those
+/// probes are only tried when asked for.
+fn probe_invocations(
+    node: &Value,
+    command_path: &[String],
+    argument_probes: bool,
+) -> Vec<(ErrorProbe, Vec<String>)> {
+    let arguments = node["arguments"].as_array().cloned().unwrap_or_default();
+    let required_count = arguments
+        .iter()
+        .filter(|argument| argument["required"].as_bool() == Some(true))
+        .count();
+    let variadic = arguments
+        .iter()
+        .any(|argument| argument["variadic"].as_bool() == Some(true));
+
+    let mut invocations = vec![(
+        ErrorProbe::UnknownFlag,
+        [command_path, &[UNKNOWN_FLAG.to_string()]].concat(),
+    )];
+    if !argument_probes {
+        return invocations;
+    }
+    if required_count > 0 {
+        invocations.push((ErrorProbe::MissingArgument, command_path.to_vec()));
+    }
+    if !variadic {
+        let mut argv = command_path.to_vec();
+        argv.extend(std::iter::repeat_n(
+            ARGUMENT_PLACEHOLDER.to_string(),
+            required_count,
+        ));
+        argv.push(EXTRA_ARGUMENT.to_string());
+        invocations.push((ErrorProbe::ExtraArgument, argv));
+    }
+    invocations
+}
+
+/// Runs every command of a parse the wrong way: with an unknown flag and, with
+/// `argument_probes`, without its required arguments and with an extra argument
+pub fn probe_errors(
+    structure: &Value,
+    options: &CrawlOptions,
+    argument_probes: bool,
+) -> ErrorProbeReport {
+    let program_name = structure["name"].as_str().unwrap_or_default().to_string();
+    let mut results = Vec::new();
+    for walked in walk_commands(structure) {
+        // External plugins run through their own executable, as when crawled
+        let command: Vec<String> = walked
+            .command
+            .split_whitespace()
+            .map(String::from)
+            .collect();
+        for (probe, argv) in probe_invocations(walked.node, &command, argument_probes) {
+            let invocation = argv.join(" ");
+            let output = execute_full_command(&invocation, options);
+            let stdout = output["stdout"].as_str().unwrap_or_default().to_string();
+            let stderr = output["stderr"].as_str().unwrap_or_default().to_string();
+            let text = format!("{}\n{}", stdout, stderr).to_lowercase();
+            results.push(ErrorProbeResult {
+                command_path: walked.command_path.clone(),
+                probe,
+                invocation,
+                suggests_help: suggests_help(&text, &program_name),
+                suggests_fix: suggests_fix(&text),
+                stdout,
+                stderr,
+                status: output["status"].as_i64().unwrap_or(-1) as i32,
+            });
+        }
+    }
+    ErrorProbeReport {
+        program: program_name,
+        version: structure["version"]
+            .as_str()
+            .unwrap_or_default()
+            .to_string(),
+        results,
+    }
+}
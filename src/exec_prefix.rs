@@ -0,0 +1,170 @@
+/// Where the arguments of each invocation go in an `--exec-prefix` template
+pub const ARGV_PLACEHOLDER: &str = "{argv}";
+
+/// A command every invocation of the parsed program is wrapped in, e.g.
+/// `docker run --rm img {argv}` or `nix shell pkg -c {argv}`.
+///
+/// A bare `{argv}` word is replaced by the arguments as separate words, so
+/// nothing needs quoting. Inside a larger or quoted word, such as
+/// `sh -c 'cd /src && {argv}'`, it is replaced by the arguments quoted for a
+/// POSIX shell.
+#[derive(Debug, Clone)]
+pub struct ExecPrefix {
+    pub template: String,
+    /// The template split into words, and whether each word was quoted
+    words: Vec<(String, bool)>,
+}
+
+impl ExecPrefix {
+    pub fn parse(template: &str) -> Result<ExecPrefix, String> {
+        let words = split_words(template)
+            .map_err(|e| format!("Invalid exec prefix '{}': {}", template, e))?;
+        if !words
+            .iter()
+            .any(|(word, _)| word.contains(ARGV_PLACEHOLDER))
+        {
+            return Err(format!(
+                "Invalid exec prefix '{}': expected {} where the program's arguments go",
+                template, ARGV_PLACEHOLDER
+            ));
+        }
+        Ok(ExecPrefix {
+            template: template.to_string(),
+            words,
+        })
+    }
+
+    /// The command line actually run for an invocation of the parsed program
+    pub fn wrap(&self, argv: &[String]) -> Vec<String> {
+        let mut wrapped = Vec::new();
+        for (word, quoted) in &self.words {
+            if word == ARGV_PLACEHOLDER && !quoted {
+                wrapped.extend(argv.iter().cloned());
+            } else {
+                let command_line = argv
+                    .iter()
+                    .map(|arg| shell_quote(arg))
+                    .collect::<Vec<_>>()
+                    .join(" ");
+                wrapped.push(word.replace(ARGV_PLACEHOLDER, &command_line));
+            }
+        }
+        wrapped
+    }
+}
+
+/// Splits a template into words the way a POSIX shell would, with single
+/// quotes, double quotes and backslash escapes
+fn split_words(template: &str) -> Result<Vec<(String, bool)>, String> {
+    let mut words = Vec::new();
+    let mut word: Option<(String, bool)> = None;
+    let mut chars = template.chars();
+
+    while let Some(c) = chars.next() {
+        match c {
+            c if c.is_whitespace() => {
+                words.extend(word.take());
+            }
+            '\'' => {
+                let (current, quoted) = word.get_or_insert_with(Default::default);
+                *quoted = true;
+                loop {
+                    match chars.next() {
+                        Some('\'') => break,
+                        Some(c) => current.push(c),
+                        None => return Err("unterminated single quote".to_string()),
+                    }
+                }
+            }
+            '"' => {
+                let (current, quoted) = word.get_or_insert_with(Default::default);
+                *quoted = true;
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some('\\') => match chars.next() {
+                            Some(c @ ('"' | '\\' | '$' | '`')) => current.push(c),
+                            Some(c) => {
+                                current.push('\\');
+                                current.push(c);
+                            }
+                            None => return Err("unterminated double quote".to_string()),
+                        },
+                        Some(c) => current.push(c),
+                        None => return Err("unterminated double quote".to_string()),
+                    }
+                }
+            }
+            '\\' => {
+                let (current, quoted) = word.get_or_insert_with(Default::default);
+                *quoted = true;
+                match chars.next() {
+                    Some(c) => current.push(c),
+                    None => return Err("trailing backslash".to_string()),
+                }
+            }
+            c => word.get_or_insert_with(Default::default).0.push(c),
+        }
+    }
+    words.extend(word);
+    Ok(words)
+}
+
+/// Quotes an argument for a POSIX shell, leaving plain words as they are
+pub fn shell_quote(arg: &str) -> String {
+    let is_plain = !arg.is_empty()
+        && arg
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "_-./=:,+@%".contains(c));
+    if is_plain {
+        arg.to_string()
+    } else {
+        format!("'{}'", arg.replace('\'', r"'\''"))
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use crate::cli_parser::extract_cli_structure;
+    use crate::models::{CrawlEnvironment, CrawlOptions};
+
+    #[test]
+    fn wrap_splices_bare_argv_and_quotes_it_inside_words() {
+        let argv = vec!["get".to_string(), "pods --all".to_string()];
+        let bare = ExecPrefix::parse("docker run --rm img {argv}").unwrap();
+        assert_eq!(
+            bare.wrap(&argv),
+            ["docker", "run", "--rm", "img", "get", "pods --all"]
+        );
+        let quoted = ExecPrefix::parse("sh -c 'cd /src && {argv}'").unwrap();
+        assert_eq!(
+            quoted.wrap(&argv),
+            ["sh", "-c", "cd /src && get 'pods --all'"]
+        );
+    }
+
+    #[test]
+    fn crawls_a_program_only_reachable_through_the_prefix() {
+        let wrapper = concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/tests/fixtures/exec_prefix/wrap.sh"
+        );
+        let environment = CrawlEnvironment {
+            exec_prefix: Some(ExecPrefix::parse(&format!("{} {{argv}}", wrapper)).unwrap()),
+            ..Default::default()
+        };
+
+        let structure = extract_cli_structure("fakecli", None, &CrawlOptions::live(environment));
+        let greet = &structure["children"]["COMMAND"]["greet"];
+        assert_eq!(greet["description"], "Print a greeting");
+        assert_eq!(greet["outputs"]["help_page"]["status"], 0);
+        let flags: Vec<_> = greet["children"]["FLAG"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|flag| flag["long"].as_str().unwrap())
+            .collect();
+        assert_eq!(flags, ["--help", "--name", "--verbose"]);
+    }
+}
@@ -0,0 +1,296 @@
+use serde_json::Value;
+use std::collections::{BTreeMap, HashSet};
+
+use crate::cli_parser::command_nodes;
+use crate::models::{CanonicalFlag, FlagAnalysis, FlagConflict, FlagConflictKind, FlagOccurrence};
+
+/// Descriptions sharing less than this share of their words mean different things
+pub const SEMANTIC_SIMILARITY: f64 = 0.25;
+/// Words that say nothing about what a flag means
+const STOP_WORDS: [&str; 12] = [
+    "a", "an", "the", "of", "to", "for", "in", "on", "one", "or", "and", "default",
+];
+
+/// The type a flag takes. `data_type` is only the placeholder the help page
+/// shows, which commands name freely, so Cobra's own type is used instead.
+fn value_kind(flag: &Value) -> String {
+    if flag["choices"].as_array().is_some_and(|c| !c.is_empty()) {
+        "enum".to_string()
+    } else if let Some(pflag_type) = flag["pflag_type"].as_str() {
+        pflag_type.to_string()
+    } else if flag["value_placeholder"].is_string() {
+        "value".to_string()
+    } else {
+        "bool".to_string()
+    }
+}
+
+/// Every flag defined by a command. Inherited copies repeat the definition,
+/// and `--help` is generated, so neither is analysed.
+fn flag_occurrences(structure: &Value) -> Vec<FlagOccurrence> {
+    let mut occurrences = Vec::new();
+    for (command_path, node) in command_nodes(structure) {
+        for flag in node["children"]["FLAG"].as_array().into_iter().flatten() {
+            let long = flag["long"].as_str().map(String::from);
+            if flag["inherited"].as_bool() == Some(true) || long.as_deref() == Some("--help") {
+                continue;
+            }
+            occurrences.push(FlagOccurrence {
+                command_path: command_path.clone(),
+                long,
+                short: flag["short"].as_str().map(String::from),
+                kind: value_kind(flag),
+                choices: flag["choices"]
+                    .as_array()
+                    .into_iter()
+                    .flatten()
+                    .filter_map(|choice| choice.as_str().map(String::from))
+                    .collect(),
+                description: flag["description"]
+                    .as_str()
+                    .unwrap_or_default()
+                    .trim()
+                    .to_string(),
+            });
+        }
+    }
+    occurrences
+}
+
+/// The words of a description that carry meaning, without its default value
+fn description_words(description: &str) -> Vec<String> {
+    let description = description.to_lowercase();
+    let description = match description.find("(default") {
+        Some(index) => &description[..index],
+        None => &description,
+    };
+    description
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty() && !STOP_WORDS.contains(word))
+        .map(String::from)
+        .collect()
+}
+
+/// Share of words two descriptions have in common (Jaccard index)
+fn similarity(a: &str, b: &str) -> f64 {
+    let a: HashSet<String> = description_words(a).into_iter().collect();
+    let b: HashSet<String> = description_words(b).into_iter().collect();
+    if a.is_empty() && b.is_empty() {
+        return 1.0;
+    }
+    a.intersection(&b).count() as f64 / a.union(&b).count() as f64
+}
+
+/// The type of a flag including its choices, so enums with different
+/// choices count as different types
+fn kind_label(occurrence: &FlagOccurrence) -> String {
+    if occurrence.choices.is_empty() {
+        occurrence.kind.clone()
+    } else {
+        format!("{} ({})", occurrence.kind, occurrence.choices.join("|"))
+    }
+}
+
+/// The most common value, the first seen winning ties
+fn most_common(values: impl Iterator<Item = String>) -> Option<String> {
+    let mut counts: Vec<(String, usize)> = Vec::new();
+    for value in values {
+        match counts.iter_mut().find(|(seen, _)| *seen == value) {
+            Some((_, count)) => *count += 1,
+            None => counts.push((value, 1)),
+        }
+    }
+    counts
+        .into_iter()
+        .rev()
+        .max_by_key(|(_, count)| *count)
+        .map(|(value, _)| value)
+}
+
+/// Distinct values with the commands having each, e.g. `string in mycli
+/// config; enum (json|yaml) in mycli get`
+fn describe_values(
+    occurrences: &[&FlagOccurrence],
+    value: impl Fn(&FlagOccurrence) -> String,
+) -> (usize, String) {
+    let mut values: Vec<(String, Vec<&str>)> = Vec::new();
+    for occurrence in occurrences {
+        let occurrence_value = value(occurrence);
+        match values
+            .iter_mut()
+            .find(|(seen, _)| *seen == occurrence_value)
+        {
+            Some((_, paths)) => paths.push(&occurrence.command_path),
+            None => values.push((occurrence_value, vec![&occurrence.command_path])),
+        }
+    }
+    let description = values
+        .iter()
+        .map(|(value, paths)| format!("{} in {}", value, paths.join(", ")))
+        .collect::<Vec<_>>()
+        .join("; ");
+    (values.len(), description)
+}
+
+fn conflict(
+    kind: FlagConflictKind,
+    flag: &str,
+    message: String,
+    occurrences: &[&FlagOccurrence],
+) -> FlagConflict {
+    FlagConflict {
+        kind,
+        flag: flag.to_string(),
+        message,
+        occurrences: occurrences.iter().map(|o| (*o).clone()).collect(),
+    }
+}
+
+fn analyze_long_flag(
+    long: &str,
+    occurrences: &[&FlagOccurrence],
+    conflicts: &mut Vec<FlagConflict>,
+) -> CanonicalFlag {
+    let kind = most_common(occurrences.iter().map(|o| kind_label(o))).unwrap_or_default();
+    let short = most_common(occurrences.iter().filter_map(|o| o.short.clone()));
+    let normalised = |o: &FlagOccurrence| description_words(&o.description).join(" ");
+    // A missing description is drift at most, so the canonical one is taken
+    // from the commands that describe the flag
+    let described: Vec<&FlagOccurrence> = occurrences
+        .iter()
+        .copied()
+        .filter(|o| !o.description.is_empty())
+        .collect();
+    let canonical_words = most_common(described.iter().map(|o| normalised(o)))
+        .or_else(|| most_common(occurrences.iter().map(|o| normalised(o))));
+    let canonical = occurrences
+        .iter()
+        .find(|o| Some(normalised(o)) == canonical_words)
+        .or(occurrences.first())
+        .expect("long flags are only analysed with occurrences");
+    let kind_occurrence = occurrences
+        .iter()
+        .find(|o| kind_label(o) == kind)
+        .unwrap_or(canonical);
+
+    let (kinds, kinds_by_command) = describe_values(occurrences, kind_label);
+    if kinds > 1 {
+        conflicts.push(conflict(
+            FlagConflictKind::TypeMismatch,
+            long,
+            format!("{} takes {}", long, kinds_by_command),
+            occurrences,
+        ));
+    }
+
+    let with_short: Vec<&FlagOccurrence> = occurrences
+        .iter()
+        .copied()
+        .filter(|o| o.short.is_some())
+        .collect();
+    let (shorts, shorts_by_command) =
+        describe_values(&with_short, |o| o.short.clone().unwrap_or_default());
+    if shorts > 1 {
+        conflicts.push(conflict(
+            FlagConflictKind::ShortMismatch,
+            long,
+            format!("{} is {}", long, shorts_by_command),
+            occurrences,
+        ));
+    }
+
+    let (descriptions, descriptions_by_command) =
+        describe_values(occurrences, |o| format!("'{}'", o.description));
+    let unrelated = described
+        .iter()
+        .any(|o| similarity(&o.description, &canonical.description) < SEMANTIC_SIMILARITY);
+    if unrelated {
+        conflicts.push(conflict(
+            FlagConflictKind::SemanticConflict,
+            long,
+            format!(
+                "{} seems to mean different things: {}",
+                long, descriptions_by_command
+            ),
+            occurrences,
+        ));
+    } else if descriptions > 1
+        && occurrences
+            .iter()
+            .any(|o| Some(normalised(o)) != canonical_words)
+    {
+        conflicts.push(conflict(
+            FlagConflictKind::DescriptionDrift,
+            long,
+            format!("{} is described as {}", long, descriptions_by_command),
+            occurrences,
+        ));
+    }
+
+    let deviating = occurrences
+        .iter()
+        .filter(|o| {
+            kind_label(o) != kind
+                || (o.short.is_some() && o.short != short)
+                || Some(normalised(o)) != canonical_words
+        })
+        .map(|o| o.command_path.clone())
+        .collect();
+    CanonicalFlag {
+        long: long.to_string(),
+        short,
+        kind: kind_occurrence.kind.clone(),
+        choices: kind_occurrence.choices.clone(),
+        description: canonical.description.clone(),
+        occurrences: occurrences.len(),
+        deviating,
+    }
+}
+
+/// Groups every flag definition of a parse by long flag and by short flag,
+/// reports where the same flag means or takes different things, and suggests
+/// the definition most commands agree on
+pub fn analyze_flags(structure: &Value) -> FlagAnalysis {
+    let occurrences = flag_occurrences(structure);
+    let mut conflicts = Vec::new();
+    let mut canonical = Vec::new();
+
+    let mut by_long: BTreeMap<&str, Vec<&FlagOccurrence>> = BTreeMap::new();
+    let mut by_short: BTreeMap<&str, Vec<&FlagOccurrence>> = BTreeMap::new();
+    for occurrence in &occurrences {
+        if let Some(long) = &occurrence.long {
+            by_long.entry(long).or_default().push(occurrence);
+        }
+        if let Some(short) = &occurrence.short {
+            by_short.entry(short).or_default().push(occurrence);
+        }
+    }
+
+    for (long, group) in by_long.iter().filter(|(_, group)| group.len() > 1) {
+        canonical.push(analyze_long_flag(long, group, &mut conflicts));
+    }
+    for (short, group) in &by_short {
+        let (longs, longs_by_command) = describe_values(group, |o| {
+            o.long.clone().unwrap_or_else(|| "no long flag".to_string())
+        });
+        if longs > 1 {
+            conflicts.push(conflict(
+                FlagConflictKind::ShortLetterConflict,
+                short,
+                format!("{} is {}", short, longs_by_command),
+                group,
+            ));
+        }
+    }
+
+    FlagAnalysis {
+        program: structure["name"].as_str().unwrap_or_default().to_string(),
+        version: structure["version"]
+            .as_str()
+            .unwrap_or_default()
+            .to_string(),
+        occurrences: occurrences.len(),
+        conflicts,
+        canonical,
+    }
+}
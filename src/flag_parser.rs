@@ -0,0 +1,272 @@
+use regex::Regex;
+use std::collections::HashSet;
+use std::sync::LazyLock;
+
+use crate::confidence::COLUMN_ALIGNED;
+use crate::models::LineFlag;
+
+/// Value type names printed by pflag (Cobra) after a flag that takes a value
+const PFLAG_TYPE_NAMES: [&str; 30] = [
+    "string",
+    "strings",
+    "stringArray",
+    "stringSlice",
+    "stringToString",
+    "stringToInt",
+    "int",
+    "int8",
+    "int16",
+    "int32",
+    "int64",
+    "ints",
+    "intSlice",
+    "uint",
+    "uint8",
+    "uint16",
+    "uint32",
+    "uint64",
+    "uints",
+    "float",
+    "float32",
+    "float64",
+    "bool",
+    "bools",
+    "duration",
+    "durationSlice",
+    "count",
+    "ip",
+    "ipNet",
+    "bytesHex",
+];
+
+// Flag definitions and descriptions are laid out in columns
+static COLUMN_RE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"\s{2,}|\t").unwrap());
+static DEPRECATED_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?i)\bdeprecated\b").unwrap());
+// "(default 1)", "(default \"red\")", "[default: red]"
+static BRACKETED_DEFAULT_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r#"[(\[]default:?\s+("[^"]*"|[^)\]]*)[)\]]"#).unwrap());
+// "default is nvidia", "defaults to 8080"
+static INLINE_DEFAULT_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r#"(?i)defaults? (?:is|to) ("[^"]*"|\S+)"#).unwrap());
+static CHOICE_SEPARATOR_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\s*(?:\||,|\bor\b)\s*").unwrap());
+static CHOICE_LIST_RES: LazyLock<[Regex; 3]> = LazyLock::new(|| {
+    let choice = r#"["'`]?[\w.\-]+["'`]?"#;
+    let choice_list = format!(r"({choice}(?:\s*(?:\||,|,?\s+or)\s*{choice})+)");
+    [
+        format!(r"(?i)one of:?\s*[\[({{]?\s*{choice_list}"),
+        format!(r"(?i)(?:possible|allowed|valid) values(?: are)?:?\s*[\[({{]?\s*{choice_list}"),
+        format!(r"(?i)(?:must be|either)\s+{choice_list}"),
+    ]
+    .map(|pattern| Regex::new(&pattern).unwrap())
+});
+// "[$FOO, $BAR]" (urfave/cli)
+static DOLLAR_ENV_LIST_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\[(\$[A-Z_][A-Z0-9_]*(?:,\s*\$[A-Z_][A-Z0-9_]*)*)\]").unwrap());
+// "[env: FOO=]" (clap), "(env FOO)", "environment variable $FOO"
+static ENV_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r"(?:\[env:\s*|(?i:\benv(?:ironment)?(?:\s+var(?:iable)?)?)[:\s]+\$?)([A-Z_][A-Z0-9_]+)",
+    )
+    .unwrap()
+});
+static REPEATED_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r"(?i)\b(repeatable|can be repeated|may be repeated|(?:specified|used|given|passed) multiple times)\b",
+    )
+    .unwrap()
+});
+
+pub fn parse_flag_line(trimmed_line: &str, section_header_name: &str) -> LineFlag {
+    let column_gap = COLUMN_RE.find(trimmed_line);
+    let (flag_spec, description) = match column_gap {
+        Some(gap) => (
+            trimmed_line[..gap.start()].to_string(),
+            trimmed_line[gap.end()..].to_string(),
+        ),
+        None => split_without_columns(trimmed_line),
+    };
+
+    let (flags, value_placeholder) = parse_flag_spec(&flag_spec);
+
+    let mut short: Option<String> = None;
+    let mut long: Option<String> = None;
+    let mut sorted_flags = flags.clone();
+    sorted_flags.sort_by_key(|f| f.len());
+    for flag in sorted_flags {
+        if flag.starts_with("--") && long.is_none() {
+            long = Some(flag);
+        } else if !flag.starts_with("--") && short.is_none() {
+            short = Some(flag);
+        }
+    }
+
+    let description = description.split_whitespace().collect::<Vec<_>>().join(" ");
+    let pflag_type = value_placeholder
+        .as_ref()
+        .filter(|placeholder| PFLAG_TYPE_NAMES.contains(&placeholder.as_str()))
+        .cloned();
+    let inherited = LineFlag::is_inherited_section(section_header_name);
+
+    LineFlag {
+        default: extract_default_value(&description),
+        choices: extract_choices(value_placeholder.as_deref(), &description),
+        env: extract_env_vars(&description),
+        deprecated: DEPRECATED_RE.is_match(&description),
+        negates: long
+            .as_ref()
+            .and_then(|l| l.strip_prefix("--no-"))
+            .map(|negated| format!("--{}", negated)),
+        repeatable: is_repeatable(
+            pflag_type.as_deref(),
+            value_placeholder.as_deref(),
+            &description,
+        ),
+        data_type: value_placeholder.clone(),
+        pflag_type,
+        value_placeholder,
+        short,
+        long,
+        description: if description.is_empty() {
+            None
+        } else {
+            Some(description)
+        },
+        parent_header: section_header_name.to_string(),
+        inherited,
+        persistent: inherited,
+        defined_in: None,
+        evidence: column_gap
+            .map(|_| vec![COLUMN_ALIGNED.to_string()])
+            .unwrap_or_default(),
+    }
+}
+
+/// Without a column gap, every token that looks like part of a flag definition
+/// is taken as the definition and the rest as the description.
+fn split_without_columns(trimmed_line: &str) -> (String, String) {
+    let parts: Vec<&str> = trimmed_line.split_whitespace().collect();
+    let spec_len = parts
+        .iter()
+        .position(|part| !part.starts_with('-') && !part.ends_with(','))
+        .unwrap_or(parts.len());
+    (parts[..spec_len].join(" "), parts[spec_len..].join(" "))
+}
+
+/// Splits a definition such as `-o, --output=FORMAT` or `--color[=WHEN]` into
+/// its flag names and the value placeholder, if any.
+fn parse_flag_spec(flag_spec: &str) -> (Vec<String>, Option<String>) {
+    let mut flags = Vec::new();
+    let mut value_placeholder: Option<String> = None;
+
+    // Commas separate flag names, except inside a `{a,b}` choice placeholder
+    let tokens = flag_spec.split_whitespace().flat_map(|token| {
+        if token.starts_with('{') {
+            vec![token.trim_end_matches(',')]
+        } else {
+            token.split(',').collect()
+        }
+    });
+
+    for token in tokens.filter(|t| !t.is_empty()) {
+        if token.starts_with('-') {
+            // `--[no-]color` defines `--color` and its negation
+            let token = token.replace("[no-]", "");
+            let (name, value) = match token.find(['=', '[']) {
+                Some(idx) => (&token[..idx], Some(&token[idx..])),
+                None => (token.as_str(), None),
+            };
+            flags.push(name.to_string());
+            if let Some(value) = value.map(|v| v.trim_matches(['=', '[', ']']))
+                && !value.is_empty()
+                && value_placeholder.is_none()
+            {
+                value_placeholder = Some(value.to_string());
+            }
+        } else if value_placeholder.is_none() {
+            value_placeholder = Some(token.to_string());
+        }
+    }
+
+    (flags, value_placeholder)
+}
+
+fn trim_value(value: &str) -> String {
+    value
+        .trim()
+        .trim_end_matches(['.', ',', ';'])
+        .trim_matches(['"', '\'', '`'])
+        .to_string()
+}
+
+fn extract_default_value(description: &str) -> Option<String> {
+    if let Some(captures) = BRACKETED_DEFAULT_RE.captures(description) {
+        return Some(trim_value(&captures[1]));
+    }
+
+    INLINE_DEFAULT_RE
+        .captures(description)
+        .map(|captures| trim_value(&captures[1]))
+        .filter(|value| !value.is_empty())
+}
+
+fn split_choices(list: &str) -> Vec<String> {
+    CHOICE_SEPARATOR_RE
+        .split(list)
+        .map(trim_value)
+        .filter(|choice| !choice.is_empty())
+        .collect()
+}
+
+fn extract_choices(value_placeholder: Option<&str>, description: &str) -> Vec<String> {
+    // Placeholders such as `{json,yaml}` or `json|yaml`
+    if let Some(placeholder) = value_placeholder {
+        let placeholder = placeholder.trim_matches(['{', '}', '<', '>']);
+        if placeholder.contains('|') || value_placeholder.is_some_and(|p| p.starts_with('{')) {
+            return split_choices(placeholder);
+        }
+    }
+
+    for choice_list_re in CHOICE_LIST_RES.iter() {
+        if let Some(captures) = choice_list_re.captures(description) {
+            return split_choices(&captures[1]);
+        }
+    }
+
+    Vec::new()
+}
+
+fn extract_env_vars(description: &str) -> Vec<String> {
+    let mut env_vars: Vec<String> = Vec::new();
+
+    for captures in DOLLAR_ENV_LIST_RE.captures_iter(description) {
+        env_vars.extend(
+            captures[1]
+                .split(',')
+                .map(|var| var.trim().trim_start_matches('$').to_string()),
+        );
+    }
+
+    env_vars.extend(
+        ENV_RE
+            .captures_iter(description)
+            .map(|captures| captures[1].to_string()),
+    );
+
+    // The same variable can be mentioned in both forms, not necessarily in a row
+    let mut seen = HashSet::new();
+    env_vars.retain(|var| seen.insert(var.clone()));
+    env_vars
+}
+
+fn is_repeatable(
+    pflag_type: Option<&str>,
+    value_placeholder: Option<&str>,
+    description: &str,
+) -> bool {
+    pflag_type.is_some_and(|t| {
+        t.ends_with("Array") || t.ends_with("Slice") || t.ends_with('s') || t == "count"
+    }) || value_placeholder.is_some_and(|p| p.ends_with("..."))
+        || REPEATED_RE.is_match(description)
+}
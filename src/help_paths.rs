@@ -0,0 +1,163 @@
+use serde_json::Value;
+
+use crate::cli_parser::{WalkedCommand, execute_full_command, walk_commands};
+use crate::models::{CommandHelpPaths, CrawlOptions, HelpForm, HelpPath, HelpPathsAudit};
+
+/// Help output with trailing whitespace and runs of blank lines removed, so
+/// only differences a reader would notice count
+fn normalise(output: &str) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+    for line in output.lines().map(str::trim_end) {
+        if line.is_empty() && lines.last().is_none_or(|last| last.is_empty()) {
+            continue;
+        }
+        lines.push(line.to_string());
+    }
+    while lines.last().is_some_and(|last| last.is_empty()) {
+        lines.pop();
+    }
+    lines
+}
+
+/// The stream the help page went to, and its normalised lines
+fn help_page(output: &Value) -> (&'static str, Vec<String>) {
+    for stream in ["stdout", "stderr"] {
+        let lines = normalise(output[stream].as_str().unwrap_or_default());
+        if !lines.is_empty() {
+            return (stream, lines);
+        }
+    }
+    ("none", Vec::new())
+}
+
+fn differences(
+    (stream, lines, status): (&str, &[String], i32),
+    (expected_stream, expected_lines, expected_status): (&str, &[String], i32),
+) -> Vec<String> {
+    let mut differences = Vec::new();
+    if status != expected_status {
+        differences.push(format!("exit {} instead of {}", status, expected_status));
+    }
+    if stream != expected_stream {
+        differences.push(format!(
+            "printed to {} instead of {}",
+            stream, expected_stream
+        ));
+    }
+    if lines != expected_lines {
+        let line = lines
+            .iter()
+            .zip(expected_lines)
+            .position(|(line, expected)| line != expected)
+            .unwrap_or(lines.len().min(expected_lines.len()));
+        differences.push(format!(
+            "content differs from line {} ({} lines instead of {}): '{}'",
+            line + 1,
+            lines.len(),
+            expected_lines.len(),
+            lines.get(line).map(String::as_str).unwrap_or_default()
+        ));
+    }
+    differences
+}
+
+/// The ways of asking a command for help, besides `--help`
+fn help_invocations(walked: &WalkedCommand, root: &Value) -> Vec<(HelpForm, String)> {
+    let (node, command) = (walked.node, &walked.command);
+    let mut invocations = Vec::new();
+
+    // `-h` may mean something else entirely, such as `--host`, so it's only
+    // tried when the command's flags pair it with `--help`
+    let has_short_help = node["children"]["FLAG"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .any(|flag| flag["short"] == "-h" && flag["long"] == "--help");
+    if has_short_help {
+        invocations.push((HelpForm::ShortFlag, format!("{} -h", command)));
+    }
+
+    let root_path = root["command_path"].as_str().unwrap_or_default();
+    let has_help_command = root["children"]["COMMAND"].get("help").is_some();
+    if has_help_command && let Some(subcommand_path) = walked.command_path.strip_prefix(root_path) {
+        invocations.push((
+            HelpForm::HelpCommand,
+            format!("{} help{}", root_path, subcommand_path),
+        ));
+    }
+
+    // A command without subcommands would run, so only groups are tried bare
+    let has_subcommands = node["children"]["COMMAND"]
+        .as_object()
+        .is_some_and(|commands| !commands.is_empty());
+    if has_subcommands {
+        invocations.push((HelpForm::NoArguments, command.to_string()));
+    }
+    invocations
+}
+
+fn audit_command(
+    walked: &WalkedCommand,
+    root: &Value,
+    options: &CrawlOptions,
+    audit: &mut HelpPathsAudit,
+) {
+    // External plugins run through their own executable, as when crawled
+    let invocation = format!("{} --help", walked.command);
+    let output = execute_full_command(&invocation, options);
+    let (stream, lines) = help_page(&output);
+    let status = output["status"].as_i64().unwrap_or(-1) as i32;
+
+    let mut paths = vec![HelpPath {
+        form: HelpForm::LongFlag,
+        invocation,
+        stream: stream.to_string(),
+        status,
+        differences: Vec::new(),
+    }];
+    for (form, invocation) in help_invocations(walked, root) {
+        let output = execute_full_command(&invocation, options);
+        let (path_stream, path_lines) = help_page(&output);
+        let path_status = output["status"].as_i64().unwrap_or(-1) as i32;
+        paths.push(HelpPath {
+            form,
+            invocation,
+            stream: path_stream.to_string(),
+            status: path_status,
+            differences: differences(
+                (path_stream, &path_lines, path_status),
+                (stream, &lines, status),
+            ),
+        });
+    }
+
+    audit.commands.push(CommandHelpPaths {
+        command_path: walked.command_path.clone(),
+        consistent: paths.iter().all(|path| path.differences.is_empty()),
+        paths,
+    });
+}
+
+/// Asks every command of a parse for help in each way a user might, and
+/// compares the answers with `--help`
+pub fn audit_help_paths(structure: &Value, options: &CrawlOptions) -> HelpPathsAudit {
+    let mut audit = HelpPathsAudit {
+        program: structure["name"].as_str().unwrap_or_default().to_string(),
+        version: structure["version"]
+            .as_str()
+            .unwrap_or_default()
+            .to_string(),
+        ..Default::default()
+    };
+    // Without the root's command path there is nothing to run
+    if structure["command_path"]
+        .as_str()
+        .is_none_or(|root_path| root_path.trim().is_empty())
+    {
+        return audit;
+    }
+    for walked in walk_commands(structure) {
+        audit_command(&walked, structure, options, &mut audit);
+    }
+    audit
+}
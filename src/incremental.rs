@@ -0,0 +1,135 @@
+use serde_json::Value;
+use std::fs;
+use std::path::PathBuf;
+
+/// FNV-1a, so hashes stay comparable across builds and toolchains
+pub fn help_page_hash(help_page: &str) -> u64 {
+    help_page.bytes().fold(0xcbf29ce484222325, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+    })
+}
+
+/// Hash of the help page captured for a parsed node, if it has one
+pub fn node_help_hash(node: &Value) -> Option<u64> {
+    node.get("outputs")
+        .and_then(|o| o.get("help_page"))
+        .and_then(|h| h.get("stdout"))
+        .and_then(|s| s.as_str())
+        .map(help_page_hash)
+}
+
+/// Finds the previous parse of a program: `./out/<prog>/<tag>/parsed.json` when
+/// a tag is given, otherwise the most recently written parse of any version.
+pub fn find_previous_structure(program_name: &str, tag: Option<&String>) -> Option<PathBuf> {
+    let base_dir = PathBuf::from("./out").join(program_name);
+    let candidate_dirs: Vec<PathBuf> = match tag {
+        Some(tag) => vec![base_dir.join(tag)],
+        None => fs::read_dir(&base_dir)
+            .ok()?
+            .flatten()
+            .filter(|entry| entry.file_type().is_ok_and(|ft| ft.is_dir()))
+            .map(|entry| entry.path())
+            .collect(),
+    };
+
+    candidate_dirs
+        .iter()
+        // Parses written before the `.json` extension was added are named `parsed`
+        .flat_map(|dir| [dir.join("parsed.json"), dir.join("parsed")])
+        .filter(|path| path.is_file())
+        .max_by_key(|path| fs::metadata(path).and_then(|m| m.modified()).ok())
+}
+
+pub fn load_previous_structure(program_name: &str, tag: Option<&String>) -> Option<Value> {
+    let path = find_previous_structure(program_name, tag)?;
+    let json = fs::read_to_string(&path).ok()?;
+    let structure = serde_json::from_str(&json).ok()?;
+    println!("Incremental parse against {}", path.display());
+    Some(structure)
+}
+
+/// Looks up the node for a command path in a parsed structure
+pub fn find_node<'a>(structure: &'a Value, command_path: &str) -> Option<&'a Value> {
+    let root_path = structure.get("command_path")?.as_str()?;
+    let relative_path = command_path.strip_prefix(root_path)?;
+    if !relative_path.is_empty() && !relative_path.starts_with(' ') {
+        return None;
+    }
+    relative_path
+        .split_whitespace()
+        .try_fold(structure, |node, name| {
+            node.get("children")?.get("COMMAND")?.get(name)
+        })
+}
+
+#[derive(Default)]
+pub struct IncrementalReport {
+    pub refreshed: Vec<String>,
+    pub added: Vec<String>,
+    pub removed: Vec<String>,
+    pub unchanged: usize,
+}
+
+/// Sorts every command of the new structure by whether its help page changed
+/// since the previous parse
+pub fn compare_help_pages(previous: &Value, current: &Value) -> IncrementalReport {
+    let mut report = IncrementalReport::default();
+    let root_path = current
+        .get("command_path")
+        .and_then(|v| v.as_str())
+        .unwrap_or_default();
+    collect_refreshed(previous, current, root_path, &mut report);
+    collect_removed(previous, current, root_path, &mut report);
+    report
+}
+
+fn child_commands(node: &Value) -> Vec<(&String, &Value)> {
+    node.get("children")
+        .and_then(|c| c.get("COMMAND"))
+        .and_then(|c| c.as_object())
+        .map(|commands| commands.iter().collect())
+        .unwrap_or_default()
+}
+
+fn collect_refreshed(
+    previous: &Value,
+    node: &Value,
+    command_path: &str,
+    report: &mut IncrementalReport,
+) {
+    match find_node(previous, command_path) {
+        None => report.added.push(command_path.to_string()),
+        Some(previous_node) if node_help_hash(previous_node) != node_help_hash(node) => {
+            report.refreshed.push(command_path.to_string())
+        }
+        Some(_) => report.unchanged += 1,
+    }
+    for (name, child) in child_commands(node) {
+        collect_refreshed(
+            previous,
+            child,
+            &format!("{} {}", command_path, name),
+            report,
+        );
+    }
+}
+
+fn collect_removed(
+    previous_node: &Value,
+    current: &Value,
+    command_path: &str,
+    report: &mut IncrementalReport,
+) {
+    if find_node(current, command_path).is_none() {
+        report.removed.push(command_path.to_string());
+        return;
+    }
+    for (name, child) in child_commands(previous_node) {
+        collect_removed(
+            child,
+            current,
+            &format!("{} {}", command_path, name),
+            report,
+        );
+    }
+}
@@ -3,13 +3,18 @@ use std::collections::HashSet;
 use std::fs;
 use std::path::PathBuf;
 
-use crate::models::CLIKeywords;
+use crate::confidence::filter_by_confidence;
+use crate::models::{CLIKeywords, LineFlag};
 
 pub fn extract_keywords_from_json(
     path: &PathBuf,
+    min_confidence: Option<f64>,
 ) -> Result<CLIKeywords, Box<dyn std::error::Error>> {
     let raw = fs::read_to_string(path).expect("Failed to read file");
-    let json: Value = serde_json::from_str(&raw).expect("Failed to read file as JSON");
+    let mut json: Value = serde_json::from_str(&raw).expect("Failed to read file as JSON");
+    if let Some(min_confidence) = min_confidence {
+        filter_by_confidence(&mut json, min_confidence);
+    }
 
     let base_program = json
         .get("name")
@@ -22,6 +27,10 @@ pub fn extract_keywords_from_json(
     let mut short_flags = HashSet::new();
     let mut long_flags = HashSet::new();
 
+    if let Some(children) = json.get("children") {
+        collect_local_flags(children, &mut short_flags, &mut long_flags);
+    }
+
     if let Some(children) = json.get("children")
         && let Some(command_map) = children.get("COMMAND").and_then(|v| v.as_object())
     {
@@ -75,8 +84,18 @@ fn walk_commands_recursively(
         }
     }
 
+    collect_local_flags(node, short_flags, long_flags);
+}
+
+/// Collects the flags a command defines itself. Inherited flags are skipped so
+/// that a global flag is only counted once, on the command that defines it.
+fn collect_local_flags(
+    node: &Value,
+    short_flags: &mut HashSet<String>,
+    long_flags: &mut HashSet<String>,
+) {
     if let Some(flags) = node.get("FLAG").and_then(|v| v.as_array()) {
-        for flag in flags {
+        for flag in flags.iter().filter(|flag| !LineFlag::is_inherited(flag)) {
             if let Some(s) = flag.get("short").and_then(|v| v.as_str()) {
                 short_flags.insert(s.to_string());
             }
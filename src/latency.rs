@@ -0,0 +1,111 @@
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap};
+use std::time::Instant;
+
+use crate::cli_parser::{execute_full_command, walk_commands};
+use crate::models::{CommandLatency, CrawlOptions, LatencyProfile};
+
+/// A command is an outlier when its median is this many times its siblings'
+pub const OUTLIER_FACTOR: f64 = 2.0;
+/// ...and at least this much slower, so millisecond jitter isn't flagged
+pub const MIN_SLOWDOWN_MS: f64 = 20.0;
+/// Fewest siblings a command is compared with
+const MIN_SIBLINGS: usize = 2;
+/// `compare` reports a command whose median grew by this factor as a regression
+pub const REGRESSION_FACTOR: f64 = 1.5;
+
+fn round_ms(ms: f64) -> f64 {
+    (ms * 10.0).round() / 10.0
+}
+
+/// Nearest-rank percentile of sorted samples
+fn percentile(sorted: &[f64], percent: f64) -> f64 {
+    if sorted.is_empty() {
+        return 0.0;
+    }
+    let rank = (percent / 100.0 * sorted.len() as f64).ceil() as usize;
+    sorted[rank.clamp(1, sorted.len()) - 1]
+}
+
+fn median(values: &[f64]) -> f64 {
+    let mut sorted = values.to_vec();
+    sorted.sort_by(f64::total_cmp);
+    percentile(&sorted, 50.0)
+}
+
+/// Whether a command this much slower than its siblings stands out
+pub fn is_slowdown(median_ms: f64, baseline_ms: f64, factor: f64) -> bool {
+    median_ms > baseline_ms * factor && median_ms - baseline_ms >= MIN_SLOWDOWN_MS
+}
+
+/// Times `runs` invocations of every command's `--help`, and flags commands
+/// much slower than their siblings. The program is run directly, so
+/// checkpoints and cassettes don't serve or record the timing runs.
+pub fn profile_latency(structure: &Value, options: &CrawlOptions, runs: usize) -> LatencyProfile {
+    let options = CrawlOptions::live(options.environment.clone());
+    let mut profile = LatencyProfile {
+        runs,
+        commands: Vec::new(),
+    };
+    let commands = walk_commands(structure);
+    for walked in &commands {
+        let invocation = format!("{} --help", walked.command);
+        let samples_ms: Vec<f64> = (0..runs)
+            .map(|_| {
+                let started = Instant::now();
+                execute_full_command(&invocation, &options);
+                round_ms(started.elapsed().as_secs_f64() * 1000.0)
+            })
+            .collect();
+        let mut sorted = samples_ms.clone();
+        sorted.sort_by(f64::total_cmp);
+        profile.commands.push(CommandLatency {
+            command_path: walked.command_path.clone(),
+            median_ms: percentile(&sorted, 50.0),
+            p95_ms: percentile(&sorted, 95.0),
+            samples_ms,
+            outlier: false,
+            sibling_median_ms: None,
+        });
+    }
+
+    // Commands are compared with the other subcommands of their parent
+    let mut siblings: BTreeMap<usize, Vec<usize>> = BTreeMap::new();
+    for (index, walked) in commands.iter().enumerate() {
+        if let Some(parent) = walked.parent {
+            siblings.entry(parent).or_default().push(index);
+        }
+    }
+    for group in siblings.into_values() {
+        for &index in &group {
+            let others: Vec<f64> = group
+                .iter()
+                .filter(|&&other| other != index)
+                .map(|&other| profile.commands[other].median_ms)
+                .collect();
+            if others.len() < MIN_SIBLINGS {
+                continue;
+            }
+            let sibling_median_ms = median(&others);
+            let command = &mut profile.commands[index];
+            command.sibling_median_ms = Some(sibling_median_ms);
+            command.outlier = is_slowdown(command.median_ms, sibling_median_ms, OUTLIER_FACTOR);
+        }
+    }
+    profile
+}
+
+/// Median latency of each command of a parse, if it was profiled
+pub fn latency_by_command(structure: &Value) -> HashMap<String, f64> {
+    structure["metadata"]["latency"]["commands"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|command| {
+            Some((
+                command["command_path"].as_str()?.to_string(),
+                command["median_ms"].as_f64()?,
+            ))
+        })
+        .collect()
+}
@@ -0,0 +1,545 @@
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::cli_parser::command_nodes;
+use crate::models::{LintConfig, LintFinding, LintReport, Severity};
+
+/// What a rule found, before its severity and suppressions are applied
+struct Violation {
+    command_path: String,
+    flag: Option<String>,
+    message: String,
+}
+
+pub struct LintRule {
+    pub id: &'static str,
+    pub description: &'static str,
+    pub severity: Severity,
+    check: fn(&Value) -> Vec<Violation>,
+}
+
+/// Every rule `clint lint` knows, with its default severity
+pub const LINT_RULES: [LintRule; 8] = [
+    LintRule {
+        id: "long-flag-kebab-case",
+        description: "Long flags are lowercase words joined by hyphens",
+        severity: Severity::Warning,
+        check: long_flag_kebab_case,
+    },
+    LintRule {
+        id: "command-description",
+        description: "Every command has a description",
+        severity: Severity::Warning,
+        check: command_description,
+    },
+    LintRule {
+        id: "flag-description",
+        description: "Every flag has a description",
+        severity: Severity::Warning,
+        check: flag_description,
+    },
+    LintRule {
+        id: "description-capitalised",
+        description: "Descriptions start with a capital letter",
+        severity: Severity::Info,
+        check: description_capitalised,
+    },
+    LintRule {
+        id: "description-trailing-period",
+        description: "Descriptions don't end with a period",
+        severity: Severity::Info,
+        check: description_trailing_period,
+    },
+    LintRule {
+        id: "short-flag-unique",
+        description: "No two flags of a command, inherited ones included, share a short flag",
+        severity: Severity::Error,
+        check: short_flag_unique,
+    },
+    LintRule {
+        id: "no-help-shadowing",
+        description: "-h and --help only ask for help",
+        severity: Severity::Error,
+        check: no_help_shadowing,
+    },
+    LintRule {
+        id: "verb-naming",
+        description: "Commands doing the same thing use the same verb, e.g. not both `list` and `ls`",
+        severity: Severity::Warning,
+        check: verb_naming,
+    },
+];
+
+/// Verbs that mean the same thing. The one most commands use is the expected one.
+const VERB_SYNONYMS: [&[&str]; 5] = [
+    &["list", "ls"],
+    &["remove", "rm", "delete", "del"],
+    &["create", "new"],
+    &["describe", "show", "inspect", "info"],
+    &["update", "edit", "modify"],
+];
+
+/// Lint configs live next to a program's parses, like overlays
+pub fn default_lint_config_path(program_name: &str) -> PathBuf {
+    PathBuf::from("./out")
+        .join(program_name)
+        .join("lint-config.json")
+}
+
+pub fn load_lint_config(path: &Path) -> Result<LintConfig, String> {
+    let json = fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read lint config {}: {}", path.display(), e))?;
+    serde_json::from_str(&json)
+        .map_err(|e| format!("Invalid lint config {}: {}", path.display(), e))
+}
+
+/// Rule ids in a config that no rule has
+pub fn unknown_rules(config: &LintConfig) -> Vec<String> {
+    let known = |id: &str| id == "*" || LINT_RULES.iter().any(|rule| rule.id == id);
+    let mut unknown: Vec<String> = config
+        .rules
+        .keys()
+        .chain(config.suppress.values().flatten())
+        .filter(|id| !known(id))
+        .cloned()
+        .collect();
+    unknown.sort();
+    unknown.dedup();
+    unknown
+}
+
+fn flags(node: &Value) -> impl Iterator<Item = &Value> {
+    node["children"]["FLAG"].as_array().into_iter().flatten()
+}
+
+/// Flags defined by the command itself. Inherited flags are linted where
+/// they are defined.
+fn own_flags(node: &Value) -> impl Iterator<Item = &Value> {
+    flags(node).filter(|flag| flag["inherited"].as_bool() != Some(true))
+}
+
+fn flag_name(flag: &Value) -> String {
+    flag["long"]
+        .as_str()
+        .or(flag["short"].as_str())
+        .unwrap_or_default()
+        .to_string()
+}
+
+fn description(item: &Value) -> Option<&str> {
+    item["description"]
+        .as_str()
+        .map(str::trim)
+        .filter(|description| !description.is_empty())
+}
+
+/// Every description of a parse, with the command and flag it belongs to
+fn descriptions(structure: &Value) -> Vec<(String, Option<String>, &str)> {
+    let mut descriptions = Vec::new();
+    for (command_path, node) in command_nodes(structure) {
+        if let Some(text) = description(node) {
+            descriptions.push((command_path.clone(), None, text));
+        }
+        for flag in own_flags(node) {
+            if let Some(text) = description(flag) {
+                descriptions.push((command_path.clone(), Some(flag_name(flag)), text));
+            }
+        }
+    }
+    descriptions
+}
+
+fn long_flag_kebab_case(structure: &Value) -> Vec<Violation> {
+    let is_kebab_case = |name: &str| {
+        name.split('-').all(|word| {
+            !word.is_empty()
+                && word
+                    .chars()
+                    .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit())
+        })
+    };
+    let mut violations = Vec::new();
+    for (command_path, node) in command_nodes(structure) {
+        for long in own_flags(node).filter_map(|flag| flag["long"].as_str()) {
+            if !is_kebab_case(long.trim_start_matches('-')) {
+                violations.push(Violation {
+                    command_path: command_path.clone(),
+                    flag: Some(long.to_string()),
+                    message: format!("{} isn't kebab-case", long),
+                });
+            }
+        }
+    }
+    violations
+}
+
+fn command_description(structure: &Value) -> Vec<Violation> {
+    command_nodes(structure)
+        .into_iter()
+        .filter(|(_, node)| description(node).is_none())
+        .map(|(command_path, _)| Violation {
+            command_path,
+            flag: None,
+            message: "command has no description".to_string(),
+        })
+        .collect()
+}
+
+fn flag_description(structure: &Value) -> Vec<Violation> {
+    let mut violations = Vec::new();
+    for (command_path, node) in command_nodes(structure) {
+        for flag in own_flags(node).filter(|flag| description(flag).is_none()) {
+            violations.push(Violation {
+                command_path: command_path.clone(),
+                flag: Some(flag_name(flag)),
+                message: format!("{} has no description", flag_name(flag)),
+            });
+        }
+    }
+    violations
+}
+
+fn description_capitalised(structure: &Value) -> Vec<Violation> {
+    descriptions(structure)
+        .into_iter()
+        .filter(|(_, _, text)| text.chars().next().is_some_and(char::is_lowercase))
+        .map(|(command_path, flag, text)| Violation {
+            command_path,
+            flag,
+            message: format!("description starts with a lowercase letter: '{}'", text),
+        })
+        .collect()
+}
+
+fn description_trailing_period(structure: &Value) -> Vec<Violation> {
+    descriptions(structure)
+        .into_iter()
+        // An ellipsis is deliberate
+        .filter(|(_, _, text)| text.ends_with('.') && !text.ends_with("..."))
+        .map(|(command_path, flag, text)| Violation {
+            command_path,
+            flag,
+            message: format!("description ends with a period: '{}'", text),
+        })
+        .collect()
+}
+
+fn short_flag_unique(structure: &Value) -> Vec<Violation> {
+    let mut violations = Vec::new();
+    for (command_path, node) in command_nodes(structure) {
+        let mut by_short: BTreeMap<&str, Vec<String>> = BTreeMap::new();
+        for flag in flags(node) {
+            if let Some(short) = flag["short"].as_str() {
+                by_short.entry(short).or_default().push(flag_name(flag));
+            }
+        }
+        for (short, names) in by_short.into_iter().filter(|(_, names)| names.len() > 1) {
+            violations.push(Violation {
+                command_path: command_path.clone(),
+                flag: Some(short.to_string()),
+                message: format!("{} is used by {}", short, names.join(", ")),
+            });
+        }
+    }
+    violations
+}
+
+fn no_help_shadowing(structure: &Value) -> Vec<Violation> {
+    let mut violations = Vec::new();
+    for (command_path, node) in command_nodes(structure) {
+        for flag in own_flags(node) {
+            let short = flag["short"].as_str();
+            let long = flag["long"].as_str();
+            let message = match (short, long) {
+                (Some("-h"), Some(long)) if long != "--help" => {
+                    format!("-h is taken by {}", long)
+                }
+                (Some("-h"), None)
+                    if !description(flag)
+                        .is_some_and(|text| text.to_lowercase().contains("help")) =>
+                {
+                    format!(
+                        "-h doesn't print help: '{}'",
+                        description(flag).unwrap_or_default()
+                    )
+                }
+                (_, Some("--help"))
+                    if description(flag)
+                        .is_some_and(|text| !text.to_lowercase().contains("help")) =>
+                {
+                    format!(
+                        "--help doesn't print help: '{}'",
+                        description(flag).unwrap_or_default()
+                    )
+                }
+                _ => continue,
+            };
+            violations.push(Violation {
+                command_path: command_path.clone(),
+                flag: Some(flag_name(flag)),
+                message,
+            });
+        }
+    }
+    violations
+}
+
+fn verb_naming(structure: &Value) -> Vec<Violation> {
+    let commands = command_nodes(structure);
+    let mut violations = Vec::new();
+    for synonyms in VERB_SYNONYMS {
+        let count = |verb: &&str| {
+            commands
+                .iter()
+                .filter(|(_, node)| node["name"].as_str() == Some(verb))
+                .count()
+        };
+        let used: Vec<(&str, usize)> = synonyms
+            .iter()
+            .map(|verb| (*verb, count(verb)))
+            .filter(|(_, count)| *count > 0)
+            .collect();
+        // The first of the most used verbs is the expected one
+        let Some(&(expected, expected_count)) = used.iter().rev().max_by_key(|(_, count)| *count)
+        else {
+            continue;
+        };
+        for (command_path, node) in &commands {
+            let Some(name) = node["name"].as_str() else {
+                continue;
+            };
+            if name != expected && synonyms.contains(&name) {
+                violations.push(Violation {
+                    command_path: command_path.clone(),
+                    flag: None,
+                    message: format!(
+                        "'{}' is used where {} commands use '{}'",
+                        name, expected_count, expected
+                    ),
+                });
+            }
+        }
+    }
+    violations
+}
+
+fn is_suppressed(config: &LintConfig, rule: &str, finding: &LintFinding) -> bool {
+    config.suppress.iter().any(|(path, rules)| {
+        let covers_rule = rules.iter().any(|id| id == rule || id == "*");
+        let covers_path = match &finding.flag {
+            Some(flag) if *path == format!("{} {}", finding.command_path, flag) => true,
+            _ => {
+                finding.command_path == *path
+                    || finding
+                        .command_path
+                        .strip_prefix(path.as_str())
+                        .is_some_and(|rest| rest.starts_with(' '))
+            }
+        };
+        covers_rule && covers_path
+    })
+}
+
+/// Runs every enabled rule over a parse, with the severities and
+/// suppressions of the config
+pub fn lint_structure(structure: &Value, config: &LintConfig) -> LintReport {
+    let mut report = LintReport {
+        program: structure["name"].as_str().unwrap_or_default().to_string(),
+        version: structure["version"]
+            .as_str()
+            .unwrap_or_default()
+            .to_string(),
+        ..Default::default()
+    };
+    for rule in &LINT_RULES {
+        let severity = config.rules.get(rule.id).copied().unwrap_or(rule.severity);
+        if severity == Severity::Off {
+            continue;
+        }
+        for violation in (rule.check)(structure) {
+            let finding = LintFinding {
+                rule: rule.id.to_string(),
+                severity,
+                command_path: violation.command_path,
+                flag: violation.flag,
+                message: violation.message,
+            };
+            if is_suppressed(config, rule.id, &finding) {
+                report.suppressed += 1;
+            } else {
+                report.findings.push(finding);
+            }
+        }
+    }
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn flag(short: Option<&str>, long: Option<&str>, description: &str) -> Value {
+        json!({ "short": short, "long": long, "description": description, "inherited": false })
+    }
+
+    /// `app` with a `list` and an `ls` command. `ls` has no `command_path`, as
+    /// when its help page couldn't be read.
+    fn structure() -> Value {
+        json!({
+            "name": "app",
+            "command_path": "app",
+            "description": "Manages things",
+            "children": {
+                "FLAG": [flag(Some("-h"), Some("--help"), "Help for app")],
+                "COMMAND": {
+                    "list": {
+                        "name": "list",
+                        "command_path": "app list",
+                        "description": "Lists things",
+                        "children": {
+                            "FLAG": [
+                                flag(Some("-h"), Some("--host"), "Host to list from"),
+                                flag(Some("-a"), Some("--all"), "Include hidden things"),
+                            ],
+                            "COMMAND": {}
+                        }
+                    },
+                    "ls": {
+                        "name": "ls",
+                        "description": "lists things.",
+                        "children": {
+                            "FLAG": [
+                                flag(Some("-h"), None, "Human readable sizes"),
+                                flag(None, Some("--dryRun"), ""),
+                            ],
+                            "COMMAND": {}
+                        }
+                    }
+                }
+            }
+        })
+    }
+
+    fn findings(report: &LintReport) -> Vec<(&str, Severity, &str, Option<&str>)> {
+        report
+            .findings
+            .iter()
+            .map(|finding| {
+                (
+                    finding.rule.as_str(),
+                    finding.severity,
+                    finding.command_path.as_str(),
+                    finding.flag.as_deref(),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn rules_find_violations_with_paths_from_the_tree() {
+        let report = lint_structure(&structure(), &LintConfig::default());
+        assert_eq!(
+            findings(&report),
+            [
+                (
+                    "long-flag-kebab-case",
+                    Severity::Warning,
+                    "app ls",
+                    Some("--dryRun")
+                ),
+                (
+                    "flag-description",
+                    Severity::Warning,
+                    "app ls",
+                    Some("--dryRun")
+                ),
+                ("description-capitalised", Severity::Info, "app ls", None),
+                (
+                    "description-trailing-period",
+                    Severity::Info,
+                    "app ls",
+                    None
+                ),
+                (
+                    "no-help-shadowing",
+                    Severity::Error,
+                    "app list",
+                    Some("--host")
+                ),
+                ("no-help-shadowing", Severity::Error, "app ls", Some("-h")),
+                ("verb-naming", Severity::Warning, "app ls", None),
+            ]
+        );
+        assert_eq!(report.suppressed, 0);
+    }
+
+    #[test]
+    fn config_overrides_severities_and_turns_rules_off() {
+        let config = LintConfig {
+            rules: BTreeMap::from([
+                ("no-help-shadowing".to_string(), Severity::Off),
+                ("verb-naming".to_string(), Severity::Off),
+                ("long-flag-kebab-case".to_string(), Severity::Off),
+                ("description-trailing-period".to_string(), Severity::Error),
+            ]),
+            ..Default::default()
+        };
+        let report = lint_structure(&structure(), &config);
+        assert_eq!(
+            findings(&report),
+            [
+                (
+                    "flag-description",
+                    Severity::Warning,
+                    "app ls",
+                    Some("--dryRun")
+                ),
+                ("description-capitalised", Severity::Info, "app ls", None),
+                (
+                    "description-trailing-period",
+                    Severity::Error,
+                    "app ls",
+                    None
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn suppressions_cover_a_command_and_below_or_a_single_flag() {
+        let config = LintConfig {
+            suppress: BTreeMap::from([
+                // Covers `app list` and `app ls`, but not `app` itself
+                ("app".to_string(), vec!["verb-naming".to_string()]),
+                ("app ls --dryRun".to_string(), vec!["*".to_string()]),
+                // Not a prefix of `app list` on a word boundary
+                ("app li".to_string(), vec!["*".to_string()]),
+            ]),
+            ..Default::default()
+        };
+        let report = lint_structure(&structure(), &config);
+        assert_eq!(
+            findings(&report),
+            [
+                ("description-capitalised", Severity::Info, "app ls", None),
+                (
+                    "description-trailing-period",
+                    Severity::Info,
+                    "app ls",
+                    None
+                ),
+                (
+                    "no-help-shadowing",
+                    Severity::Error,
+                    "app list",
+                    Some("--host")
+                ),
+                ("no-help-shadowing", Severity::Error, "app ls", Some("-h")),
+            ]
+        );
+        assert_eq!(report.suppressed, 3);
+    }
+}
@@ -1,22 +1,49 @@
+mod argument_parser;
+mod cassette;
+mod checkpoint;
 mod cli_navigator_toolkit;
 mod cli_parser;
 mod comparison;
+mod confidence;
+mod error_probe;
+mod exec_prefix;
+mod flag_analysis;
+mod flag_parser;
+mod help_paths;
+mod incremental;
 mod keyword_extractor;
+mod latency;
+mod lint;
 mod models;
 mod naive_tooltip_content_generator;
+mod overlay;
+mod plugins;
 mod replicator;
+mod report_formats;
 mod summary_generator;
+mod terminal_audit;
+mod typo_probe;
 mod usage_parser;
+mod variants;
 
+use checkpoint::Checkpoint;
 use cli_navigator_toolkit::{
-    run_cli_compare, run_cli_parser, run_cli_replicator, run_get_template_web_files,
-    run_interactive_serve, run_keyword_extractor, run_summary_generator,
+    print_lint_rules, run_cli_compare, run_cli_parser, run_cli_replicator, run_error_probe,
+    run_flag_analysis, run_get_template_web_files, run_help_paths_audit, run_interactive_serve,
+    run_keyword_extractor, run_lint, run_summary_generator, run_terminal_audit, run_typo_probe,
+};
+use exec_prefix::ExecPrefix;
+use incremental::load_previous_structure;
+use models::{
+    CrawlEnvironment, CrawlOptions, ExecutionMode, FileOutputFormat, ParseOutput, ReportFormat,
+    ReportOptions, Severity,
 };
-use models::FileOutputFormat;
 use naive_tooltip_content_generator::write_ts_file;
+use plugins::path_dirs;
 use std::{env::current_dir, path::PathBuf};
+use variants::parse_variant;
 
-use clap::{CommandFactory, Parser, Subcommand};
+use clap::{Args, CommandFactory, Parser, Subcommand};
 
 #[derive(Parser)]
 #[command(version, about, long_about = None)]
@@ -28,32 +55,19 @@ struct Cli {
 #[derive(Subcommand)]
 enum Commands {
     /// Parses a CLI program written with the Cobra library and generates output in the specified format
-    Parse {
-        #[arg(value_name = "PROGRAM_NAME")]
-        name: String,
-        #[arg(short = 'o', long = "output", value_name = "PATH")]
-        output_file: Option<PathBuf>,
-        #[arg(
-            short,
-            long,
-            value_name = "FORMAT",
-            help = "Output format: json (default), zod, json-schema, or ts-dir"
-        )]
-        format: Option<String>,
-        #[arg(
-            short,
-            long,
-            value_name = "TAG",
-            help = "Custom tag for organizing different versions/states of the CLI"
-        )]
-        tag: Option<String>,
-    },
+    Parse(Box<ParseArgs>),
     /// Extracts unique keywords (commands, subcommands, and flags) from a parsed JSON file (outputs as CSV)
     UniqueKeywords {
         #[arg(value_name = "INPUT_JSON")]
         input_json: Option<PathBuf>,
         #[arg(short, long, value_name = "OUTPUT_PATH")]
         output_path: Option<PathBuf>,
+        #[arg(
+            long,
+            value_name = "SCORE",
+            help = "Leave out commands, flags and arguments parsed with a confidence below SCORE (0 to 1)"
+        )]
+        min_confidence: Option<f64>,
     },
     /// Generates a summary of the CLI structure
     Summary {
@@ -63,6 +77,12 @@ enum Commands {
         output_path: Option<PathBuf>,
         #[arg(short, long, value_name = "FORMAT")]
         format: Option<String>,
+        #[arg(
+            long,
+            value_name = "SCORE",
+            help = "Leave out commands, flags and arguments parsed with a confidence below SCORE (0 to 1)"
+        )]
+        min_confidence: Option<f64>,
     },
     /// Downloads web interface templates to ~/.config/clint/templates/default for customization (optional - embedded templates used by default)
     GetTemplate {
@@ -77,6 +97,18 @@ enum Commands {
         port: Option<u16>,
         #[arg(short, long, value_name = "JSON_FILE")]
         input: Option<PathBuf>,
+        #[arg(
+            long,
+            value_name = "SCORE",
+            help = "Highlight commands, flags and arguments parsed with a confidence below SCORE (0 to 1)"
+        )]
+        min_confidence: Option<f64>,
+        #[arg(
+            long,
+            value_name = "NAME",
+            help = "Only show the commands and flags found in this variant"
+        )]
+        variant: Option<String>,
     },
     /// Generates a replica of the CLI program in RustLang using the clap library
     Replicate {
@@ -88,6 +120,12 @@ enum Commands {
         keep_help_flags: bool,
         #[arg(long, default_value_t = false)]
         keep_verbose_flags: bool,
+        #[arg(
+            long,
+            value_name = "SCORE",
+            help = "Leave out commands, flags and arguments parsed with a confidence below SCORE (0 to 1)"
+        )]
+        min_confidence: Option<f64>,
     },
     /// Generates the TypeScript file for the NaiveTooltip component
     NaiveTooltip {
@@ -95,6 +133,67 @@ enum Commands {
         input_json: Option<PathBuf>,
         #[arg(short, long, value_name = "OUTPUT_PATH")]
         output_path: Option<PathBuf>,
+        #[arg(
+            long,
+            value_name = "SCORE",
+            help = "Leave out tooltips whose confidence is below SCORE (0 to 1)"
+        )]
+        min_confidence: Option<f64>,
+    },
+    /// Probes how the parsed program behaves when it is used wrong
+    Probe {
+        #[command(subcommand)]
+        probe: ProbeCommands,
+    },
+    /// Audits how consistently the parsed program presents itself
+    Audit {
+        #[command(subcommand)]
+        audit: AuditCommands,
+    },
+    /// Analyzes the parsed program's structure across commands
+    Analyze {
+        #[command(subcommand)]
+        analyze: AnalyzeCommands,
+    },
+    /// Checks a parsed CLI structure against CLI design rules
+    Lint {
+        #[arg(value_name = "INPUT_JSON", required_unless_present = "list_rules")]
+        input_json: Option<PathBuf>,
+        #[arg(
+            short,
+            long,
+            value_name = "OUTPUT_PATH",
+            help = "Where to save the findings (defaults to lint.json next to INPUT_JSON)"
+        )]
+        output_path: Option<PathBuf>,
+        #[arg(
+            long,
+            value_name = "PATH",
+            help = "Rule severities and suppressions (defaults to ./out/<prog>/lint-config.json)"
+        )]
+        config: Option<PathBuf>,
+        #[arg(long, help = "List the rules and their default severities")]
+        list_rules: bool,
+        #[arg(
+            long,
+            value_name = "FORMAT",
+            help = "Also write the findings as a report: sarif, junit"
+        )]
+        report: Option<String>,
+        #[arg(
+            long,
+            value_name = "PATH",
+            requires = "report",
+            help = "Where to save the report (defaults to lint.sarif or lint.junit.xml next to INPUT_JSON)"
+        )]
+        report_path: Option<PathBuf>,
+        #[arg(
+            long,
+            value_name = "SEVERITY",
+            default_value = "error",
+            help = "Exit with 1 when a finding is at least this severe: error, warning, info, never"
+        )]
+        fail_on: String,
     },
     /// Compares two parsed CLI structures and displays differences
     Compare {
@@ -119,20 +218,463 @@ enum Commands {
             help = "Output format to compare: json (default), ts-dir"
         )]
         format: Option<String>,
+        #[arg(
+            long,
+            value_name = "NAME",
+            help = "Only compare the commands and flags found in this variant (json only)"
+        )]
+        variant: Option<String>,
+        #[arg(
+            long,
+            value_name = "FORMAT",
+            help = "Also write the changes as a report: sarif, junit (json only)"
+        )]
+        report: Option<String>,
+        #[arg(
+            long,
+            value_name = "PATH",
+            requires = "report",
+            help = "Where to save the report (defaults to ./out/<prog>/compare-<TAG1>-<TAG2>.sarif or .junit.xml)"
+        )]
+        report_path: Option<PathBuf>,
+        #[arg(
+            long,
+            value_name = "SEVERITY",
+            default_value = "never",
+            help = "Exit with 1 when a change is at least this severe: error, warning, info, never (json only)"
+        )]
+        fail_on: String,
     },
 }
 
+#[derive(Args)]
+struct ParseArgs {
+    #[arg(value_name = "PROGRAM_NAME")]
+    name: String,
+    #[arg(short = 'o', long = "output", value_name = "PATH")]
+    output_file: Option<PathBuf>,
+    #[arg(
+        short,
+        long,
+        value_name = "FORMAT",
+        help = "Output format: json (default), zod, json-schema, or ts-dir"
+    )]
+    format: Option<String>,
+    #[arg(
+        short,
+        long,
+        value_name = "TAG",
+        help = "Custom tag for organizing different versions/states of the CLI"
+    )]
+    tag: Option<String>,
+    #[arg(
+        long,
+        value_name = "COLUMNS",
+        default_value_t = 80,
+        help = "Terminal width reported to the parsed program through COLUMNS"
+    )]
+    columns: u16,
+    #[arg(
+        long = "env",
+        value_name = "KEY=VALUE",
+        help = "Extra environment variable for the parsed program (can be repeated)"
+    )]
+    env: Vec<String>,
+    #[arg(
+        long,
+        value_name = "DIR",
+        help = "Working directory to run the parsed program from"
+    )]
+    cwd: Option<PathBuf>,
+    #[arg(
+        long,
+        value_name = "TEMPLATE",
+        conflicts_with_all = ["replay", "from_text", "from_dir"],
+        help = "Run the parsed program through a wrapper command, e.g. 'docker run --rm img {argv}'"
+    )]
+    exec_prefix: Option<String>,
+    #[arg(
+        long,
+        value_name = "DIR",
+        conflicts_with_all = ["replay", "from_text", "from_dir"],
+        help = "Save every invocation of the parsed program into a cassette directory"
+    )]
+    record: Option<PathBuf>,
+    #[arg(
+        long,
+        value_name = "DIR",
+        conflicts_with_all = ["from_text", "from_dir"],
+        help = "Parse from the invocations saved in a cassette directory instead of running the program"
+    )]
+    replay: Option<PathBuf>,
+    #[arg(
+        long,
+        value_name = "FILE",
+        conflicts_with = "from_dir",
+        help = "Parse a single captured help page from a file, or from stdin with '-'"
+    )]
+    from_text: Option<PathBuf>,
+    #[arg(
+        long,
+        value_name = "DIR",
+        help = "Parse captured help pages from a directory of '<command path>.txt' files"
+    )]
+    from_dir: Option<PathBuf>,
+    #[arg(
+        long,
+        help = "Reuse the previous parse of commands whose help page is unchanged. Every --help is still run, only parsing is skipped"
+    )]
+    incremental: bool,
+    #[arg(
+        long,
+        conflicts_with_all = ["replay", "from_text", "from_dir"],
+        help = "Continue an interrupted crawl from its checkpoint"
+    )]
+    resume: bool,
+    #[arg(
+        long,
+        value_name = "FILE",
+        help = "Also write the crawl report to a separate JSON file"
+    )]
+    report: Option<PathBuf>,
+    #[arg(
+        long,
+        value_name = "FILE",
+        help = "Overlay of corrections to apply after crawling (defaults to ./out/<prog>/overlay.json)"
+    )]
+    overlay: Option<PathBuf>,
+    #[arg(
+        long = "variant",
+        value_name = "NAME[:KEY=VALUE,...]",
+        conflicts_with = "incremental",
+        help = "Crawl once per variant, with its environment variables, and merge the results (can be repeated)"
+    )]
+    variants: Vec<String>,
+    #[arg(
+        long,
+        conflicts_with_all = ["replay", "from_text", "from_dir"],
+        help = "Add the <prog>-<name> executables on PATH as external subcommands"
+    )]
+    plugins: bool,
+    #[arg(
+        long = "plugin-dir",
+        value_name = "DIR",
+        conflicts_with_all = ["replay", "from_text", "from_dir"],
+        help = "Look for <prog>-<name> executables in DIR instead of PATH (can be repeated)"
+    )]
+    plugin_dirs: Vec<PathBuf>,
+    #[arg(
+        long,
+        conflicts_with_all = ["replay", "from_text", "from_dir"],
+        help = "Time every command's --help and store the latencies in the metadata"
+    )]
+    profile: bool,
+    #[arg(
+        long,
+        value_name = "RUNS",
+        default_value_t = 5,
+        value_parser = clap::value_parser!(u64).range(1..),
+        help = "How many times each command is timed with --profile"
+    )]
+    profile_runs: u64,
+    #[arg(
+        long,
+        value_name = "SCORE",
+        help = "Leave out commands, flags and arguments parsed with a confidence below SCORE (0 to 1) from a ts-dir export"
+    )]
+    min_confidence: Option<f64>,
+}
+
+#[derive(Subcommand)]
+enum ProbeCommands {
+    /// Runs every command with an unknown flag (and, if asked, a missing and an extra argument), and records the errors printed
+    Errors {
+        #[arg(value_name = "INPUT_JSON")]
+        input_json: Option<PathBuf>,
+        #[arg(
+            short,
+            long,
+            value_name = "OUTPUT_PATH",
+            help = "Where to save the results (defaults to error-probes.json next to INPUT_JSON)"
+        )]
+        output_path: Option<PathBuf>,
+        #[arg(
+            long,
+            value_name = "TEMPLATE",
+            help = "Run the program through a wrapper command instead of the one it was parsed with"
+        )]
+        exec_prefix: Option<String>,
+        #[arg(
+            long,
+            help = "Also run every command without its required arguments and with an extra one. Commands that accept them really run, so use a sandbox"
+        )]
+        argument_probes: bool,
+    },
+    /// Tries typos of every subcommand and long flag, and records whether the right correction is suggested
+    Typos {
+        #[arg(value_name = "INPUT_JSON")]
+        input_json: Option<PathBuf>,
+        #[arg(
+            short,
+            long,
+            value_name = "OUTPUT_PATH",
+            help = "Where to save the results (defaults to typo-probes.json next to INPUT_JSON)"
+        )]
+        output_path: Option<PathBuf>,
+        #[arg(
+            long,
+            value_name = "TEMPLATE",
+            help = "Run the program through a wrapper command instead of the one it was parsed with"
+        )]
+        exec_prefix: Option<String>,
+    },
+}
+
+#[derive(Subcommand)]
+enum AnalyzeCommands {
+    /// Groups flags by name and short letter, and reports conflicting meanings and types with a suggested definition
+    Flags {
+        #[arg(value_name = "INPUT_JSON")]
+        input_json: PathBuf,
+        #[arg(
+            short,
+            long,
+            value_name = "OUTPUT_PATH",
+            help = "Where to save the analysis (defaults to flag-analysis.json next to INPUT_JSON)"
+        )]
+        output_path: Option<PathBuf>,
+    },
+}
+
+#[derive(Subcommand)]
+enum AuditCommands {
+    /// Asks every command for help with -h, --help, '<prog> help' and no arguments, and reports where the answers differ
+    HelpPaths {
+        #[arg(value_name = "INPUT_JSON")]
+        input_json: Option<PathBuf>,
+        #[arg(
+            short,
+            long,
+            value_name = "OUTPUT_PATH",
+            help = "Where to save the results (defaults to help-paths.json next to INPUT_JSON)"
+        )]
+        output_path: Option<PathBuf>,
+        #[arg(
+            long,
+            value_name = "TEMPLATE",
+            help = "Run the program through a wrapper command instead of the one it was parsed with"
+        )]
+        exec_prefix: Option<String>,
+    },
+    /// Prints every command's help at 40, 80 and 200 columns and with colour terminals, and reports long lines and escape codes
+    Terminal {
+        #[arg(value_name = "INPUT_JSON")]
+        input_json: Option<PathBuf>,
+        #[arg(
+            short,
+            long,
+            value_name = "OUTPUT_PATH",
+            help = "Where to save the results (defaults to terminal-audit.json next to INPUT_JSON)"
+        )]
+        output_path: Option<PathBuf>,
+        #[arg(
+            long,
+            value_name = "TEMPLATE",
+            help = "Run the program through a wrapper command instead of the one it was parsed with"
+        )]
+        exec_prefix: Option<String>,
+        #[arg(
+            long,
+            value_name = "N",
+            default_value_t = 100,
+            help = "Longest help line allowed, however wide the terminal"
+        )]
+        max_line_length: usize,
+    },
+}
+
+/// The report options of `lint` and `compare`, or None if one is invalid
+fn report_options(
+    report: &Option<String>,
+    report_path: &Option<PathBuf>,
+    fail_on: &str,
+) -> Option<ReportOptions> {
+    let format = match report {
+        Some(report) => match ReportFormat::from_str(report) {
+            Some(format) => Some(format),
+            None => {
+                println!(
+                    "Unknown report format '{}', expected sarif or junit",
+                    report
+                );
+                return None;
+            }
+        },
+        None => None,
+    };
+    let Some(fail_on) = Severity::from_str(fail_on) else {
+        println!(
+            "Unknown severity '{}', expected error, warning, info or never",
+            fail_on
+        );
+        return None;
+    };
+    Some(ReportOptions {
+        format,
+        path: report_path.clone(),
+        fail_on,
+    })
+}
+
+/// The parse a probe or audit runs against, and the exec prefix to override
+/// the one it was parsed with
+fn probe_target<'a>(
+    input_json: &'a Option<PathBuf>,
+    exec_prefix: &Option<String>,
+) -> Option<(&'a PathBuf, Option<ExecPrefix>)> {
+    let Some(input_json) = input_json else {
+        println!("No input JSON file provided.");
+        return None;
+    };
+    match exec_prefix.as_deref().map(ExecPrefix::parse).transpose() {
+        Ok(exec_prefix) => Some((input_json, exec_prefix)),
+        Err(e) => {
+            println!("{}", e);
+            None
+        }
+    }
+}
+
 fn main() {
     let cli = Cli::parse();
 
     match &cli.command {
-        Some(Commands::Parse {
-            name,
-            output_file,
-            format,
-            tag,
-        }) => {
-            run_cli_parser(name, output_file.as_ref(), format.as_ref(), tag.as_ref());
+        Some(Commands::Parse(args)) => {
+            let ParseArgs {
+                name,
+                output_file,
+                format,
+                tag,
+                columns,
+                env,
+                cwd,
+                exec_prefix,
+                record,
+                replay,
+                from_text,
+                from_dir,
+                incremental,
+                resume,
+                report,
+                overlay,
+                variants,
+                plugins,
+                plugin_dirs,
+                profile,
+                profile_runs,
+                min_confidence,
+            } = args.as_ref();
+            let mut extra_env = Vec::new();
+            for pair in env {
+                match pair.split_once('=') {
+                    Some((key, value)) if !key.is_empty() => {
+                        extra_env.push((key.to_string(), value.to_string()));
+                    }
+                    _ => {
+                        println!(
+                            "Invalid environment variable '{}', expected KEY=VALUE",
+                            pair
+                        );
+                        return;
+                    }
+                }
+            }
+            let exec_prefix = match exec_prefix.as_deref().map(ExecPrefix::parse).transpose() {
+                Ok(exec_prefix) => exec_prefix,
+                Err(e) => {
+                    println!("{}", e);
+                    return;
+                }
+            };
+            let mut parsed_variants = Vec::new();
+            for spec in variants {
+                match parse_variant(spec) {
+                    Ok(variant) => parsed_variants.push(variant),
+                    Err(e) => {
+                        println!("{}", e);
+                        return;
+                    }
+                }
+            }
+            let execution_mode = if let Some(dir) = record {
+                ExecutionMode::Record(dir.clone())
+            } else if let Some(dir) = replay {
+                ExecutionMode::Replay(dir.clone())
+            } else if let Some(dir) = from_dir {
+                ExecutionMode::HelpTextDir(dir.clone())
+            } else if let Some(file) = from_text {
+                let from_stdin = file.as_os_str() == "-";
+                let help_text = if from_stdin {
+                    std::io::read_to_string(std::io::stdin())
+                } else {
+                    std::fs::read_to_string(file)
+                };
+                match help_text {
+                    Ok(help_text) => {
+                        ExecutionMode::HelpText(help_text, (!from_stdin).then(|| file.clone()))
+                    }
+                    Err(e) => {
+                        println!("Failed to read help text from {}: {}", file.display(), e);
+                        return;
+                    }
+                }
+            } else {
+                ExecutionMode::Live
+            };
+            let previous_structure = if *incremental {
+                let previous_structure = load_previous_structure(name, tag.as_ref());
+                if previous_structure.is_none() {
+                    println!("No previous parse of {} found, parsing everything", name);
+                }
+                previous_structure
+            } else {
+                None
+            };
+            // Only crawls that run the program are worth resuming
+            let checkpoint = match execution_mode {
+                ExecutionMode::Live | ExecutionMode::Record(_) if *resume => {
+                    Some(Checkpoint::resume(name))
+                }
+                ExecutionMode::Live | ExecutionMode::Record(_) => Some(Checkpoint::start(name)),
+                _ => None,
+            };
+            let options = CrawlOptions {
+                environment: CrawlEnvironment {
+                    columns: *columns,
+                    extra_env,
+                    working_dir: cwd.clone(),
+                    exec_prefix,
+                },
+                execution_mode,
+                previous_structure,
+                checkpoint,
+                variants: parsed_variants,
+                plugin_dirs: if !plugin_dirs.is_empty() {
+                    Some(plugin_dirs.clone())
+                } else if *plugins {
+                    Some(path_dirs())
+                } else {
+                    None
+                },
+                profile_runs: profile.then_some(*profile_runs as usize),
+            };
+            let output = ParseOutput {
+                path: output_file.as_ref(),
+                format: format.as_ref(),
+                tag: tag.as_ref(),
+                min_confidence: *min_confidence,
+            };
+            run_cli_parser(name, &output, report.as_ref(), overlay.as_ref(), &options);
         }
         Some(Commands::GetTemplate { force }) => {
             run_get_template_web_files(*force);
@@ -140,6 +682,7 @@ fn main() {
         Some(Commands::UniqueKeywords {
             input_json,
             output_path,
+            min_confidence,
         }) => {
             let input_json = match input_json {
                 Some(path) => path,
@@ -163,12 +706,18 @@ fn main() {
                     )),
             };
 
-            run_keyword_extractor(input_json, output_path, FileOutputFormat::Csv);
+            run_keyword_extractor(
+                input_json,
+                output_path,
+                FileOutputFormat::Csv,
+                *min_confidence,
+            );
         }
         Some(Commands::Summary {
             input_json,
             output_path,
             format,
+            min_confidence,
         }) => {
             let input_json = match input_json {
                 Some(path) => path,
@@ -216,20 +765,30 @@ fn main() {
                 input_json,
                 out_path,
                 output_file_format.expect("Failed to get output format"),
+                *min_confidence,
             );
         }
         Some(Commands::Serve {
             template,
             port,
             input,
+            min_confidence,
+            variant,
         }) => {
-            run_interactive_serve(template.as_ref(), *port, input.as_ref());
+            run_interactive_serve(
+                template.as_ref(),
+                *port,
+                input.as_ref(),
+                *min_confidence,
+                variant.as_ref(),
+            );
         }
         Some(Commands::Replicate {
             input_json,
             output_path,
             keep_help_flags,
             keep_verbose_flags,
+            min_confidence,
         }) => {
             let input_json = match input_json {
                 Some(path) => path,
@@ -252,11 +811,18 @@ fn main() {
                         input_json_file_name.unwrap_or("output")
                     )),
             };
-            run_cli_replicator(input_json, out_path, *keep_help_flags, *keep_verbose_flags);
+            run_cli_replicator(
+                input_json,
+                out_path,
+                *keep_help_flags,
+                *keep_verbose_flags,
+                *min_confidence,
+            );
         }
         Some(Commands::NaiveTooltip {
             input_json,
             output_path,
+            min_confidence,
         }) => {
             let input_json = match input_json {
                 Some(path) => path,
@@ -279,15 +845,126 @@ fn main() {
                         input_json_file_name.unwrap_or("output")
                     )),
             };
-            write_ts_file(input_json, out_path).expect("Failed to write TypeScript file");
+            write_ts_file(input_json, out_path, *min_confidence)
+                .expect("Failed to write TypeScript file");
         }
         Some(Commands::Compare {
             name,
             from,
             to,
             format,
+            variant,
+            report,
+            report_path,
+            fail_on,
         }) => {
-            run_cli_compare(name, from.as_ref(), to.as_ref(), format.as_ref());
+            let Some(report_options) = report_options(report, report_path, fail_on) else {
+                return;
+            };
+            run_cli_compare(
+                name,
+                from.as_ref(),
+                to.as_ref(),
+                format.as_ref(),
+                variant.as_ref(),
+                &report_options,
+            );
+        }
+        Some(Commands::Probe { probe }) => {
+            let (ProbeCommands::Errors {
+                input_json,
+                output_path,
+                exec_prefix,
+                ..
+            }
+            | ProbeCommands::Typos {
+                input_json,
+                output_path,
+                exec_prefix,
+            }) = probe;
+            let Some((input_json, exec_prefix)) = probe_target(input_json, exec_prefix) else {
+                return;
+            };
+            match probe {
+                ProbeCommands::Errors {
+                    argument_probes, ..
+                } => {
+                    run_error_probe(
+                        input_json,
+                        output_path.as_ref(),
+                        exec_prefix,
+                        *argument_probes,
+                    );
+                }
+                ProbeCommands::Typos { .. } => {
+                    run_typo_probe(input_json, output_path.as_ref(), exec_prefix);
+                }
+            }
+        }
+        Some(Commands::Lint {
+            input_json,
+            output_path,
+            config,
+            list_rules,
+            report,
+            report_path,
+            fail_on,
+        }) => {
+            if *list_rules {
+                print_lint_rules();
+                return;
+            }
+            let Some(report_options) = report_options(report, report_path, fail_on) else {
+                return;
+            };
+            if let Some(input_json) = input_json {
+                run_lint(
+                    input_json,
+                    output_path.as_ref(),
+                    config.as_ref(),
+                    &report_options,
+                );
+            }
+        }
+        Some(Commands::Analyze {
+            analyze:
+                AnalyzeCommands::Flags {
+                    input_json,
+                    output_path,
+                },
+        }) => {
+            run_flag_analysis(input_json, output_path.as_ref());
+        }
+        Some(Commands::Audit { audit }) => {
+            let (AuditCommands::HelpPaths {
+                input_json,
+                output_path,
+                exec_prefix,
+            }
+            | AuditCommands::Terminal {
+                input_json,
+                output_path,
+                exec_prefix,
+                ..
+            }) = audit;
+            let Some((input_json, exec_prefix)) = probe_target(input_json, exec_prefix) else {
+                return;
+            };
+            match audit {
+                AuditCommands::HelpPaths { .. } => {
+                    run_help_paths_audit(input_json, output_path.as_ref(), exec_prefix);
+                }
+                AuditCommands::Terminal {
+                    max_line_length, ..
+                } => {
+                    run_terminal_audit(
+                        input_json,
+                        output_path.as_ref(),
+                        exec_prefix,
+                        *max_line_length,
+                    );
+                }
+            }
         }
         None => {
             let mut cmd = Cli::command();
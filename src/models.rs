@@ -1,7 +1,10 @@
-use std::{fs, path::PathBuf};
+use std::{collections::BTreeMap, fs, path::PathBuf};
 
 use serde::{Deserialize, Serialize};
-use serde_json::Value;
+use serde_json::{Value, json};
+
+use crate::checkpoint::Checkpoint;
+use crate::exec_prefix::ExecPrefix;
 
 #[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
 pub enum ComponentType {
@@ -11,6 +14,17 @@ pub enum ComponentType {
     Group,
     AlternativeGroup,
     KeyValuePair,
+    /// `[options]` or `[flags]`, standing for every flag of the command
+    OptionsShortcut,
+    /// `--`, after which every token is taken as a positional argument
+    Separator,
+}
+
+/// Byte range of a usage component within its usage line
+#[derive(Debug, Clone, Copy, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct Span {
+    pub start: usize,
+    pub end: usize,
 }
 
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
@@ -22,6 +36,24 @@ pub struct UsageComponent {
     pub key_value: bool,
     pub alternatives: Vec<UsageComponent>,
     pub children: Vec<UsageComponent>,
+    /// Value placeholder bound to a flag, from `--flag=<val>` or `--flag <val>`
+    #[serde(default)]
+    pub value: Option<String>,
+    #[serde(default)]
+    pub span: Span,
+}
+
+/// A problem found while parsing a usage line. The parser recovers and keeps
+/// going, so the components are still returned alongside the errors.
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct UsageParseError {
+    pub message: String,
+    pub span: Span,
+}
+
+pub struct ParsedUsage {
+    pub components: Vec<UsageComponent>,
+    pub errors: Vec<UsageParseError>,
 }
 
 #[derive(Serialize, Debug)]
@@ -43,6 +75,10 @@ pub struct CLISummary {
     pub total_subcommand_count: usize,
     pub total_short_flag_count: usize,
     pub total_long_flag_count: usize,
+    pub total_alias_count: usize,
+    pub total_example_count: usize,
+    pub total_help_topic_count: usize,
+    pub total_argument_count: usize,
 }
 
 pub enum FileOutputFormat {
@@ -52,6 +88,16 @@ pub enum FileOutputFormat {
     Csv,
 }
 
+/// Where and how `parse` writes the structure it produces
+pub struct ParseOutput<'a> {
+    pub path: Option<&'a PathBuf>,
+    pub format: Option<&'a String>,
+    pub tag: Option<&'a String>,
+    /// Leaves commands, flags and arguments scoring lower out of a TypeScript
+    /// directory export
+    pub min_confidence: Option<f64>,
+}
+
 pub enum ParseOutputFormat {
     Json,
     JsonSchema,
@@ -97,6 +143,579 @@ impl FileOutputFormat {
         }
     }
 }
+/// Environment applied to every command spawned while crawling a CLI, so that
+/// help output does not depend on the terminal `clint` itself runs in.
+#[derive(Debug, Clone)]
+pub struct CrawlEnvironment {
+    pub columns: u16,
+    pub extra_env: Vec<(String, String)>,
+    pub working_dir: Option<PathBuf>,
+    /// Command the program is run through, e.g. to crawl it inside a container
+    pub exec_prefix: Option<ExecPrefix>,
+}
+
+impl Default for CrawlEnvironment {
+    fn default() -> Self {
+        CrawlEnvironment {
+            columns: 80,
+            extra_env: Vec::new(),
+            working_dir: None,
+            exec_prefix: None,
+        }
+    }
+}
+
+impl CrawlEnvironment {
+    /// Variables set on each child process. User-provided variables come last
+    /// so they can override the defaults.
+    pub fn env_vars(&self) -> Vec<(String, String)> {
+        let mut vars = vec![
+            ("COLUMNS".to_string(), self.columns.to_string()),
+            ("NO_COLOR".to_string(), "1".to_string()),
+            ("TERM".to_string(), "dumb".to_string()),
+            ("LANG".to_string(), "C".to_string()),
+            ("LC_ALL".to_string(), "C".to_string()),
+        ];
+        vars.extend(self.extra_env.iter().cloned());
+        vars
+    }
+
+    /// The environment a parse was crawled with, from its `metadata.environment`
+    pub fn from_metadata(metadata: &Value) -> CrawlEnvironment {
+        let vars = metadata["vars"].as_object();
+        let columns = vars
+            .and_then(|vars| vars.get("COLUMNS"))
+            .and_then(|columns| columns.as_str())
+            .and_then(|columns| columns.parse().ok())
+            .unwrap_or(80);
+        // Defaults repeated here are set to the same value twice, which is harmless
+        let extra_env = vars
+            .into_iter()
+            .flatten()
+            .filter(|(key, _)| key.as_str() != "COLUMNS")
+            .filter_map(|(key, value)| Some((key.clone(), value.as_str()?.to_string())))
+            .collect();
+        CrawlEnvironment {
+            columns,
+            extra_env,
+            working_dir: metadata["working_dir"].as_str().map(PathBuf::from),
+            exec_prefix: metadata["exec_prefix"]
+                .as_str()
+                .and_then(|template| ExecPrefix::parse(template).ok()),
+        }
+    }
+
+    pub fn to_metadata(&self) -> Value {
+        let vars: serde_json::Map<String, Value> = self
+            .env_vars()
+            .into_iter()
+            .map(|(key, value)| (key, json!(value)))
+            .collect();
+        json!({
+            "vars": vars,
+            "working_dir": self.working_dir.as_ref().map(|dir| dir.display().to_string()),
+            "exec_prefix": self.exec_prefix.as_ref().map(|prefix| prefix.template.clone()),
+        })
+    }
+}
+
+/// Where the crawler gets the output of each invocation from
+#[derive(Clone)]
+pub enum ExecutionMode {
+    /// Run the parsed program
+    Live,
+    /// Run the parsed program and save every invocation into a cassette directory
+    Record(PathBuf),
+    /// Serve the invocations saved in a cassette directory instead of running anything
+    Replay(PathBuf),
+    /// Serve a captured help page as the output of `<program> --help`, and the
+    /// file it was read from unless it came from stdin
+    HelpText(String, Option<PathBuf>),
+    /// Serve `<command path>.txt` files as the output of `<command path> --help`
+    HelpTextDir(PathBuf),
+}
+
+impl ExecutionMode {
+    /// Each variant of a crawl keeps its cassette or captured help pages in a
+    /// subdirectory named after it
+    pub fn for_variant(&self, variant_name: &str) -> ExecutionMode {
+        match self {
+            ExecutionMode::Record(dir) => ExecutionMode::Record(dir.join(variant_name)),
+            ExecutionMode::Replay(dir) => ExecutionMode::Replay(dir.join(variant_name)),
+            ExecutionMode::HelpTextDir(dir) => ExecutionMode::HelpTextDir(dir.join(variant_name)),
+            ExecutionMode::Live | ExecutionMode::HelpText(..) => self.clone(),
+        }
+    }
+
+    /// Where captured help pages were read from, so findings can point at them
+    pub fn help_source(&self) -> Value {
+        match self {
+            ExecutionMode::HelpTextDir(dir) => json!({ "dir": dir.display().to_string() }),
+            ExecutionMode::HelpText(_, Some(file)) => {
+                json!({ "file": file.display().to_string() })
+            }
+            _ => Value::Null,
+        }
+    }
+}
+
+/// A build or configuration of the parsed program, crawled with its own
+/// environment variables, e.g. `experimental:EXPERIMENTAL=1`
+#[derive(Debug, Clone)]
+pub struct Variant {
+    pub name: String,
+    pub env: Vec<(String, String)>,
+}
+
+pub struct CrawlOptions {
+    pub environment: CrawlEnvironment,
+    pub execution_mode: ExecutionMode,
+    /// Structure from an earlier parse. Commands whose help page hasn't changed
+    /// are taken from it instead of being crawled again.
+    pub previous_structure: Option<Value>,
+    /// Where invocations of the parsed program are saved as the crawl goes, so
+    /// an interrupted crawl can be resumed
+    pub checkpoint: Option<Checkpoint>,
+    /// Crawled one after the other and merged into one structure. Empty for a
+    /// single crawl.
+    pub variants: Vec<Variant>,
+    /// Directories searched for `<prog>-<name>` executables to add as
+    /// external subcommands. `None` leaves plugins out.
+    pub plugin_dirs: Option<Vec<PathBuf>>,
+    /// How many times each command's `--help` is timed for the latency
+    /// profile. `None` skips profiling.
+    pub profile_runs: Option<usize>,
+}
+
+impl CrawlOptions {
+    /// Options for running the program directly, as the probes and audits do
+    pub fn live(environment: CrawlEnvironment) -> CrawlOptions {
+        CrawlOptions {
+            environment,
+            execution_mode: ExecutionMode::Live,
+            previous_structure: None,
+            checkpoint: None,
+            variants: Vec::new(),
+            plugin_dirs: None,
+            profile_runs: None,
+        }
+    }
+
+    /// Options for crawling a single variant, on top of the common environment
+    pub fn for_variant(&self, variant: &Variant) -> CrawlOptions {
+        let mut environment = self.environment.clone();
+        environment.extra_env.extend(variant.env.iter().cloned());
+        CrawlOptions {
+            environment,
+            execution_mode: self.execution_mode.for_variant(&variant.name),
+            previous_structure: None,
+            checkpoint: self.checkpoint.clone(),
+            variants: Vec::new(),
+            plugin_dirs: self.plugin_dirs.clone(),
+            profile_runs: self.profile_runs,
+        }
+    }
+}
+
+/// A single run of the parsed program, as saved in a cassette
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Invocation {
+    pub argv: Vec<String>,
+    pub env: BTreeMap<String, String>,
+    pub working_dir: Option<PathBuf>,
+    pub stdout: String,
+    pub stderr: String,
+    pub status: i32,
+}
+
+/// What happened while crawling a single command
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct NodeReport {
+    pub command_path: String,
+    pub invocation: String,
+    pub duration_ms: u64,
+    pub status: i32,
+    /// The first lines of stderr, if the command printed anything there
+    pub stderr_excerpt: Option<String>,
+    /// Taken unchanged from the previous parse (`--incremental`)
+    pub reused: bool,
+    /// The variant crawled, when several were merged
+    #[serde(default)]
+    pub variant: Option<String>,
+    /// Lines that ended up in `OTHER`
+    pub unclassified_lines: Vec<String>,
+    pub warnings: Vec<String>,
+}
+
+/// How long a command takes to print its help page
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct CommandLatency {
+    pub command_path: String,
+    pub median_ms: f64,
+    pub p95_ms: f64,
+    pub samples_ms: Vec<f64>,
+    /// Much slower than its sibling commands
+    pub outlier: bool,
+    /// Median of the sibling commands' medians, when there are enough to compare with
+    pub sibling_median_ms: Option<f64>,
+}
+
+/// Startup latency of every command, from `clint parse --profile`
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct LatencyProfile {
+    pub runs: usize,
+    pub commands: Vec<CommandLatency>,
+}
+
+/// One entry per crawled command, in crawl order
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct CrawlReport {
+    pub nodes: Vec<NodeReport>,
+}
+
+/// A way of getting a command wrong, tried by `clint probe errors`
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorProbe {
+    UnknownFlag,
+    MissingArgument,
+    ExtraArgument,
+}
+
+/// What the program printed for one wrong invocation
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ErrorProbeResult {
+    pub command_path: String,
+    pub probe: ErrorProbe,
+    pub invocation: String,
+    pub stdout: String,
+    pub stderr: String,
+    pub status: i32,
+    /// Points the user at `--help` or a help command
+    pub suggests_help: bool,
+    /// Says what was expected or what to do instead, e.g. "did you mean"
+    pub suggests_fix: bool,
+}
+
+/// Results of `clint probe errors`, in crawl order, so reports of two
+/// versions can be diffed
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct ErrorProbeReport {
+    pub program: String,
+    pub version: String,
+    pub results: Vec<ErrorProbeResult>,
+}
+
+/// How a typo was derived from a subcommand or flag name
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum TypoKind {
+    /// Two neighbouring letters swapped
+    Transposition,
+    MissingLetter,
+    /// `-` typed as `_` or the other way around
+    WrongSeparator,
+}
+
+/// How the program answered a typo
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum TypoOutcome {
+    /// Suggested the name the typo was derived from
+    Corrected,
+    /// Suggested something else
+    Misdirected,
+    /// Failed without suggesting anything
+    Unhelpful,
+    /// Exited successfully without suggesting anything
+    Accepted,
+}
+
+/// One typo tried by `clint probe typos`
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct TypoProbeResult {
+    pub command_path: String,
+    /// The subcommand name or long flag the typo was derived from
+    pub target: String,
+    pub kind: TypoKind,
+    pub typo: String,
+    pub invocation: String,
+    pub status: i32,
+    pub outcome: TypoOutcome,
+}
+
+/// How many typos of a command's subcommands and flags were corrected
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct TypoCoverage {
+    pub command_path: String,
+    pub subcommands_corrected: usize,
+    pub subcommands_probed: usize,
+    pub flags_corrected: usize,
+    pub flags_probed: usize,
+}
+
+/// Results of `clint probe typos`, with the coverage of each command in crawl order
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct TypoProbeReport {
+    pub program: String,
+    pub version: String,
+    pub results: Vec<TypoProbeResult>,
+    pub coverage: Vec<TypoCoverage>,
+}
+
+/// A way of asking a command for its help page
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum HelpForm {
+    /// `<cmd> --help`, what every other form is compared against
+    LongFlag,
+    /// `<cmd> -h`
+    ShortFlag,
+    /// `<prog> help <subcommand path>`
+    HelpCommand,
+    /// `<cmd>` on its own, tried for commands with subcommands
+    NoArguments,
+}
+
+/// What one form of asking for help printed, compared to `--help`
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct HelpPath {
+    pub form: HelpForm,
+    pub invocation: String,
+    /// `stdout`, `stderr` or `none`, whichever the help page was printed to
+    pub stream: String,
+    pub status: i32,
+    /// How the output differs from `--help`, empty when it's the same
+    pub differences: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct CommandHelpPaths {
+    pub command_path: String,
+    pub consistent: bool,
+    pub paths: Vec<HelpPath>,
+}
+
+/// Results of `clint audit help-paths`, in crawl order
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct HelpPathsAudit {
+    pub program: String,
+    pub version: String,
+    pub commands: Vec<CommandHelpPaths>,
+}
+
+/// What `clint audit terminal` checks help output for
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum TerminalCheck {
+    /// Lines wider than the terminal width the program was given
+    Width,
+    /// Lines longer than the line-length budget
+    LineLength,
+    /// Escape codes although `NO_COLOR` is set
+    NoColor,
+    /// Escape codes although the output isn't a terminal
+    NonTty,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct TerminalFinding {
+    pub check: TerminalCheck,
+    /// The variables the help page was printed with, e.g. `COLUMNS=40`
+    pub environment: String,
+    pub message: String,
+    /// The first offending lines, prefixed with their line number
+    pub lines: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct CommandTerminalFindings {
+    pub command_path: String,
+    pub findings: Vec<TerminalFinding>,
+}
+
+/// Results of `clint audit terminal`, in crawl order
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct TerminalAudit {
+    pub program: String,
+    pub version: String,
+    pub max_line_length: usize,
+    pub commands: Vec<CommandTerminalFindings>,
+}
+
+/// How much a lint finding or a change between versions matters. `Off`
+/// disables a lint rule, and as a `--fail-on` threshold never fails.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "snake_case")]
+pub enum Severity {
+    Off,
+    Info,
+    Warning,
+    Error,
+}
+
+impl Severity {
+    pub fn from_str(severity: &str) -> Option<Self> {
+        match severity.to_lowercase().as_str() {
+            "off" | "never" => Some(Severity::Off),
+            "info" | "note" => Some(Severity::Info),
+            "warning" => Some(Severity::Warning),
+            "error" => Some(Severity::Error),
+            _ => None,
+        }
+    }
+
+    /// Whether results of this severity reach a `--fail-on` threshold
+    pub fn reaches(&self, threshold: Severity) -> bool {
+        threshold != Severity::Off && *self >= threshold
+    }
+}
+
+/// Machine-readable formats for lint findings and version changes
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ReportFormat {
+    Sarif,
+    Junit,
+}
+
+impl ReportFormat {
+    pub fn from_str(format: &str) -> Option<Self> {
+        match format.to_lowercase().as_str() {
+            "sarif" => Some(ReportFormat::Sarif),
+            "junit" => Some(ReportFormat::Junit),
+            _ => None,
+        }
+    }
+
+    pub fn get_file_extension(&self) -> &'static str {
+        match self {
+            ReportFormat::Sarif => "sarif",
+            ReportFormat::Junit => "junit.xml",
+        }
+    }
+}
+
+/// Where `clint lint` and `clint compare` write a SARIF or JUnit report, and
+/// the severity that makes them exit with 1
+pub struct ReportOptions {
+    pub format: Option<ReportFormat>,
+    pub path: Option<PathBuf>,
+    pub fail_on: Severity,
+}
+
+/// A captured help page file and the line a result points at
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct SourceLocation {
+    pub file: String,
+    pub line: Option<usize>,
+}
+
+/// A lint finding or a change between versions, as SARIF and JUnit report it
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ReportResult {
+    pub rule_id: String,
+    pub severity: Severity,
+    pub command_path: String,
+    pub flag: Option<String>,
+    pub message: String,
+    /// Only known for structures parsed from captured help pages
+    pub location: Option<SourceLocation>,
+}
+
+/// Per-project lint settings, from `./out/<prog>/lint-config.json`
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct LintConfig {
+    /// Severity overrides keyed by rule id
+    #[serde(default)]
+    pub rules: BTreeMap<String, Severity>,
+    /// Rule ids (or `*`) to ignore, keyed by a command path, which covers the
+    /// command and everything below it, or a command path ending in a flag
+    #[serde(default)]
+    pub suppress: BTreeMap<String, Vec<String>>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct LintFinding {
+    pub rule: String,
+    pub severity: Severity,
+    pub command_path: String,
+    /// The flag the finding is about, if any
+    pub flag: Option<String>,
+    pub message: String,
+}
+
+/// Results of `clint lint`, rule by rule
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct LintReport {
+    pub program: String,
+    pub version: String,
+    pub findings: Vec<LintFinding>,
+    /// How many findings the config's `suppress` entries hid
+    pub suppressed: usize,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum FlagConflictKind {
+    /// A long flag takes different kinds of values, e.g. a path and an enum
+    TypeMismatch,
+    /// A long flag's descriptions have little in common, so it means
+    /// different things in different commands
+    SemanticConflict,
+    /// A long flag's descriptions are worded differently
+    DescriptionDrift,
+    /// A long flag has different short forms
+    ShortMismatch,
+    /// A short flag stands for different long flags
+    ShortLetterConflict,
+}
+
+/// A flag as defined by one command
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct FlagOccurrence {
+    pub command_path: String,
+    pub long: Option<String>,
+    pub short: Option<String>,
+    /// `bool`, `enum`, the parsed data type or `value`
+    pub kind: String,
+    pub choices: Vec<String>,
+    pub description: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct FlagConflict {
+    pub kind: FlagConflictKind,
+    /// The long or short flag the occurrences share
+    pub flag: String,
+    pub message: String,
+    pub occurrences: Vec<FlagOccurrence>,
+}
+
+/// The definition most commands agree on for a long flag, and the commands
+/// that differ from it
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct CanonicalFlag {
+    pub long: String,
+    pub short: Option<String>,
+    pub kind: String,
+    pub choices: Vec<String>,
+    pub description: String,
+    pub occurrences: usize,
+    pub deviating: Vec<String>,
+}
+
+/// Results of `clint analyze flags`
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct FlagAnalysis {
+    pub program: String,
+    pub version: String,
+    /// Flag definitions analysed, inherited copies not included
+    pub occurrences: usize,
+    pub conflicts: Vec<FlagConflict>,
+    /// For every long flag defined by more than one command
+    pub canonical: Vec<CanonicalFlag>,
+}
+
 pub struct OutputFile {
     pub path: PathBuf,
     pub format: FileOutputFormat,
@@ -135,6 +754,10 @@ pub enum ChildLineType {
     Flag,
     Command,
     Usage,
+    Aliases,
+    Example,
+    HelpTopic,
+    Argument,
     Other,
 }
 
@@ -151,9 +774,51 @@ pub struct LineCommand {
 pub struct LineFlag {
     pub short: Option<String>,
     pub long: Option<String>,
+    /// Value name printed after the flag, as read before pflag types were known
     pub data_type: Option<String>,
+    /// Value type printed by pflag (Cobra), e.g. `string` or `stringArray`
+    pub pflag_type: Option<String>,
     pub description: Option<String>,
     pub parent_header: String,
+    /// Listed under a `Global Flags:` section, i.e. defined by an ancestor
+    pub inherited: bool,
+    /// Available to every subcommand of the command that defines it
+    pub persistent: bool,
+    /// Command path of the ancestor defining an inherited flag
+    pub defined_in: Option<String>,
+    pub default: Option<String>,
+    /// Accepted values, e.g. from `one of: json|yaml`
+    pub choices: Vec<String>,
+    /// Environment variables the flag can be set from
+    pub env: Vec<String>,
+    pub deprecated: bool,
+    /// For `--no-x` flags, the flag being negated (`--x`)
+    pub negates: Option<String>,
+    pub repeatable: bool,
+    /// Value name printed after the flag, e.g. `string` or `FILE`
+    pub value_placeholder: Option<String>,
+    /// Where the flag was found, see `confidence`
+    #[serde(default)]
+    pub evidence: Vec<String>,
+}
+
+impl LineFlag {
+    pub fn is_inherited_section(section_header: &str) -> bool {
+        let section_header = section_header.to_lowercase();
+        section_header.contains("global") || section_header.contains("inherited")
+    }
+
+    /// Whether a serialized flag was inherited from an ancestor. Structures
+    /// parsed before `inherited` existed fall back to the section header.
+    pub fn is_inherited(flag: &Value) -> bool {
+        flag.get("inherited")
+            .and_then(|v| v.as_bool())
+            .unwrap_or_else(|| {
+                flag.get("parent_header")
+                    .and_then(|v| v.as_str())
+                    .is_some_and(LineFlag::is_inherited_section)
+            })
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -161,6 +826,62 @@ pub struct LineUsage {
     pub usage_string: String,
     pub parent_header: String,
     pub usage_components: Vec<UsageComponent>,
+    #[serde(default)]
+    pub parse_errors: Vec<UsageParseError>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct LineAliases {
+    pub aliases: Vec<String>,
+    pub parent_header: String,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct LineExample {
+    pub line_contents: String,
+    pub parent_header: String,
+}
+
+/// An entry of the `Additional help topics:` section. Topics are help-only
+/// pages, so they are kept apart from the commands and never crawled.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct LineHelpTopic {
+    pub name: String,
+    pub description: String,
+    pub command_path: String,
+    pub parent_header: String,
+}
+
+/// A usage example, pairing the `# comment` lines with the command line that
+/// follows them.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct CommandExample {
+    pub comment: Option<String>,
+    pub command_line: String,
+}
+
+/// An entry of an `Arguments:` section, e.g. `  <file>...   Files to read`
+#[derive(Serialize, Deserialize, Debug)]
+pub struct LineArgument {
+    pub name: String,
+    pub description: Option<String>,
+    pub required: bool,
+    pub variadic: bool,
+    pub parent_header: String,
+}
+
+/// A positional argument of a command, derived from its usage line and
+/// completed with the description from an `Arguments:` section.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct CommandArgument {
+    pub name: String,
+    pub position: usize,
+    pub required: bool,
+    pub variadic: bool,
+    pub description: Option<String>,
+    /// Where the argument was found, see `confidence`
+    #[serde(default)]
+    pub evidence: Vec<String>,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -175,6 +896,10 @@ pub enum OutputLine {
     Usage(LineUsage),
     Command(LineCommand),
     Flag(LineFlag),
+    Aliases(LineAliases),
+    Example(LineExample),
+    HelpTopic(LineHelpTopic),
+    Argument(LineArgument),
 }
 
 pub struct ChildLine {
@@ -15,6 +15,9 @@ struct ToolTipContents {
     examples: Option<Vec<serde_json::Value>>, // You can replace with concrete type
     references: Option<Vec<serde_json::Value>>, // You can replace with concrete type
     alias: Option<String>,
+    /// Confidence of the parsed node the tooltip is for, when known
+    #[serde(default)]
+    confidence: Option<f64>,
 }
 
 type TokenObject = HashMap<String, ToolTipContents>;
@@ -127,10 +130,23 @@ fn serialize_token_object_to_ts(token_map: &TokenObject) -> String {
     out
 }
 
-pub fn write_ts_file(cli_json_path: &PathBuf, output_ts_path: &PathBuf) -> std::io::Result<()> {
+/// Writes the tooltips as TypeScript. With `min_confidence`, tooltips scoring
+/// lower are left out; tooltips without a score are kept.
+pub fn write_ts_file(
+    cli_json_path: &PathBuf,
+    output_ts_path: &PathBuf,
+    min_confidence: Option<f64>,
+) -> std::io::Result<()> {
     let cli_file = File::open(cli_json_path).expect("Failed to open CLI structure JSON file");
-    let token_data: TokenObject =
+    let mut token_data: TokenObject =
         serde_json::from_reader(cli_file).expect("Failed to parse CLI structure JSON");
+    if let Some(min_confidence) = min_confidence {
+        token_data.retain(|_, contents| {
+            contents
+                .confidence
+                .is_none_or(|confidence| confidence >= min_confidence)
+        });
+    }
 
     let mut file = File::create(output_ts_path).expect("Failed to create TypeScript output file");
     file.write_all(TYPE_DEFS.as_bytes())
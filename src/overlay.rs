@@ -0,0 +1,104 @@
+use serde_json::Value;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Overlays live next to a program's parses, so they survive the next `clint parse`
+pub fn default_overlay_path(program_name: &str) -> PathBuf {
+    PathBuf::from("./out")
+        .join(program_name)
+        .join("overlay.json")
+}
+
+pub fn load_overlay(path: &Path) -> Result<Value, String> {
+    let json = fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read overlay {}: {}", path.display(), e))?;
+    let overlay: Value = serde_json::from_str(&json)
+        .map_err(|e| format!("Invalid overlay {}: {}", path.display(), e))?;
+    if !overlay.is_object() {
+        return Err(format!(
+            "Invalid overlay {}: expected an object keyed by command path",
+            path.display()
+        ));
+    }
+    Ok(overlay)
+}
+
+/// JSON Merge Patch: objects are merged key by key, `null` removes a key and
+/// anything else replaces the target
+pub fn merge_patch(target: &mut Value, patch: &Value) {
+    let Some(patch_map) = patch.as_object() else {
+        *target = patch.clone();
+        return;
+    };
+    if !target.is_object() {
+        *target = Value::Object(serde_json::Map::new());
+    }
+    let target_map = target.as_object_mut().unwrap();
+    for (key, value) in patch_map {
+        if value.is_null() {
+            target_map.remove(key);
+        } else {
+            merge_patch(target_map.entry(key.clone()).or_insert(Value::Null), value);
+        }
+    }
+}
+
+fn find_node_mut<'a>(structure: &'a mut Value, command_path: &str) -> Option<&'a mut Value> {
+    let root_path = structure.get("command_path")?.as_str()?.to_string();
+    let relative_path = command_path.strip_prefix(root_path.as_str())?;
+    if !relative_path.is_empty() && !relative_path.starts_with(' ') {
+        return None;
+    }
+    relative_path
+        .split_whitespace()
+        .try_fold(structure, |node, name| {
+            node.get_mut("children")?.get_mut("COMMAND")?.get_mut(name)
+        })
+}
+
+fn find_flag_mut<'a>(node: &'a mut Value, flag_name: &str) -> Option<&'a mut Value> {
+    node.get_mut("children")?
+        .get_mut("FLAG")?
+        .as_array_mut()?
+        .iter_mut()
+        .find(|flag| {
+            ["long", "short"]
+                .iter()
+                .any(|key| flag.get(key).and_then(|v| v.as_str()) == Some(flag_name))
+        })
+}
+
+/// Applies hand-written corrections and additions to a parse, returning the
+/// overlay keys whose command or flag isn't in the structure (anymore).
+///
+/// Keys are command paths and values are JSON Merge Patches (RFC 7386) applied
+/// to that command. A key ending in a flag, e.g. `kubectl get --output`, patches
+/// that flag of the command instead.
+///
+/// ```json
+/// {
+///   "kubectl get": { "owner": "cli-team", "stability": "stable" },
+///   "kubectl get --output": { "choices": ["json", "yaml", "wide"] }
+/// }
+/// ```
+pub fn apply_overlay(structure: &mut Value, overlay: &Value) -> Vec<String> {
+    let mut missing = Vec::new();
+    let Some(entries) = overlay.as_object() else {
+        return missing;
+    };
+
+    for (key, patch) in entries {
+        let target = match key.rsplit_once(' ') {
+            Some((command_path, flag_name)) if flag_name.starts_with('-') => {
+                find_node_mut(structure, command_path)
+                    .and_then(|node| find_flag_mut(node, flag_name))
+            }
+            _ => find_node_mut(structure, key),
+        };
+        match target {
+            Some(target) => merge_patch(target, patch),
+            None => missing.push(key.clone()),
+        }
+    }
+    missing
+}
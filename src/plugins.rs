@@ -0,0 +1,78 @@
+use std::collections::BTreeMap;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// An external subcommand: a `<prog>-<name>` executable that the program runs
+/// for `<prog> <name>`, like `git-lfs`, `kubectl-krew` or `cargo-edit`
+#[derive(Debug, Clone)]
+pub struct Plugin {
+    pub name: String,
+    /// What is run to crawl the plugin. Plugins found on PATH are run by name,
+    /// so cassettes and captured help pages don't depend on where they live.
+    pub command: String,
+}
+
+/// The directories on PATH, in order
+pub fn path_dirs() -> Vec<PathBuf> {
+    env::var_os("PATH")
+        .map(|path| env::split_paths(&path).collect())
+        .unwrap_or_default()
+}
+
+#[cfg(unix)]
+fn is_executable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    fs::metadata(path).is_ok_and(|m| m.is_file() && m.permissions().mode() & 0o111 != 0)
+}
+
+#[cfg(not(unix))]
+fn is_executable(path: &Path) -> bool {
+    path.is_file()
+}
+
+/// Finds the `<prog>-<name>` executables in `dirs`, sorted by name. When a name
+/// is found in several directories the first one wins, as it would on PATH.
+pub fn discover_plugins(program_name: &str, dirs: &[PathBuf]) -> Vec<Plugin> {
+    let program_name = Path::new(program_name)
+        .file_name()
+        .and_then(|name| name.to_str())
+        .unwrap_or(program_name);
+    let prefix = format!("{}-", program_name);
+    let on_path = path_dirs();
+
+    let mut plugins: BTreeMap<String, Plugin> = BTreeMap::new();
+    for dir in dirs {
+        let Ok(entries) = fs::read_dir(dir) else {
+            continue;
+        };
+        let mut file_names: Vec<String> = entries
+            .flatten()
+            .filter_map(|entry| entry.file_name().to_str().map(String::from))
+            .collect();
+        file_names.sort();
+
+        for file_name in file_names {
+            let Some(name) = file_name.strip_prefix(&prefix) else {
+                continue;
+            };
+            let path = dir.join(&file_name);
+            if name.is_empty() || plugins.contains_key(name) || !is_executable(&path) {
+                continue;
+            }
+            let command = if on_path.contains(dir) {
+                file_name.clone()
+            } else {
+                path.display().to_string()
+            };
+            plugins.insert(
+                name.to_string(),
+                Plugin {
+                    name: name.to_string(),
+                    command,
+                },
+            );
+        }
+    }
+    plugins.into_values().collect()
+}
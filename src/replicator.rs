@@ -2,6 +2,8 @@ use clap::Parser;
 use serde::Deserialize;
 use std::{collections::HashMap, env, fs, path::PathBuf, process::Command as ShellCommand};
 
+use crate::confidence::filter_by_confidence;
+
 /// CLI generator arguments
 #[derive(Parser)]
 #[command(
@@ -30,12 +32,16 @@ struct CliSpec {
     name: String,
     description: String,
     version: String,
+    #[serde(default)]
+    arguments: Vec<ArgumentSpec>,
     children: ChildrenSpec,
 }
 
 #[derive(Deserialize)]
 struct ChildrenSpec {
+    #[serde(default, alias = "COMMAND")]
     commands: HashMap<String, CommandSpec>,
+    #[serde(default, alias = "FLAG")]
     flags: Vec<FlagSpec>,
 }
 
@@ -43,9 +49,30 @@ struct ChildrenSpec {
 struct CommandSpec {
     name: String,
     description: String,
+    #[serde(default)]
+    aliases: Vec<String>,
+    #[serde(default)]
+    examples: Vec<ExampleSpec>,
+    #[serde(default)]
+    arguments: Vec<ArgumentSpec>,
     children: ChildrenSpec,
 }
 
+#[derive(Deserialize)]
+struct ArgumentSpec {
+    name: String,
+    position: usize,
+    required: bool,
+    variadic: bool,
+    description: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct ExampleSpec {
+    comment: Option<String>,
+    command_line: String,
+}
+
 #[derive(Deserialize)]
 struct FlagSpec {
     short: Option<String>,
@@ -53,6 +80,13 @@ struct FlagSpec {
     data_type: Option<String>,
     description: Option<String>,
     required: Option<bool>,
+    #[serde(default)]
+    inherited: bool,
+    #[serde(default)]
+    persistent: bool,
+    default: Option<String>,
+    #[serde(default)]
+    choices: Vec<String>,
 }
 
 pub fn replicate(
@@ -60,9 +94,15 @@ pub fn replicate(
     output_path: &PathBuf,
     keep_help_flags: bool,
     keep_verbose_flags: bool,
+    min_confidence: Option<f64>,
 ) -> Result<(), Box<dyn std::error::Error>> {
     let json = fs::read_to_string(input_json).expect("Failed to read CLI Structure JSON file");
-    let spec: CliSpec = serde_json::from_str(&json)
+    let mut structure: serde_json::Value = serde_json::from_str(&json)
+        .expect("Failed to parse CLI Structure JSON file. Make sure it is valid JSON.");
+    if let Some(min_confidence) = min_confidence {
+        filter_by_confidence(&mut structure, min_confidence);
+    }
+    let spec: CliSpec = serde_json::from_value(structure)
         .expect("Failed to parse CLI Structure JSON file. Make sure it is valid JSON.");
     let output_dir: PathBuf = PathBuf::from(output_path);
     // Scaffold new Rust project
@@ -152,6 +192,90 @@ fn generate_command_handler_files(
     Ok(())
 }
 
+/// Builder calls for a command's aliases and its examples, shown after the help text
+fn aliases_and_examples_calls(cmd_spec: &CommandSpec) -> String {
+    let mut calls = String::new();
+    if !cmd_spec.aliases.is_empty() {
+        calls.push_str(&format!(
+            ".visible_aliases([{}])",
+            cmd_spec
+                .aliases
+                .iter()
+                .map(|alias| format!("\"{}\"", alias.replace('"', "\\\"")))
+                .collect::<Vec<_>>()
+                .join(", ")
+        ));
+    }
+    if !cmd_spec.examples.is_empty() {
+        let mut examples = String::from("Examples:");
+        for example in &cmd_spec.examples {
+            if let Some(comment) = &example.comment {
+                examples.push_str(&format!("\n  # {}", comment));
+            }
+            examples.push_str(&format!("\n  {}", example.command_line));
+        }
+        calls.push_str(&format!(
+            ".after_help(\"{}\")",
+            examples
+                .replace('\\', "\\\\")
+                .replace('"', "\\\"")
+                .replace('\n', "\\n")
+        ));
+    }
+    calls
+}
+
+/// Positional arguments, in the order they are given on the command line
+fn argument_calls(arguments: &[ArgumentSpec]) -> String {
+    let mut calls = String::new();
+    for argument in arguments {
+        let action = if argument.variadic {
+            ".num_args(1..).action(ArgAction::Append)"
+        } else {
+            ".action(ArgAction::Set)"
+        };
+        calls.push_str(&format!(
+            ".arg(Arg::new(\"{id}\").value_name(\"{name}\").help(\"{help}\").index({index}){action}.required({required}))",
+            id = argument.name.to_lowercase().replace('-', "_"),
+            name = argument.name,
+            help = argument
+                .description
+                .as_deref()
+                .unwrap_or_default()
+                .replace('"', "\\\""),
+            index = argument.position + 1,
+            action = action,
+            required = argument.required
+        ));
+    }
+    calls
+}
+
+/// Default value and accepted values, only for flags that take a value
+fn value_calls(flag: &FlagSpec, action: &str) -> String {
+    if action != "ArgAction::Set" {
+        return String::new();
+    }
+    let mut calls = String::new();
+    if let Some(default) = &flag.default {
+        calls.push_str(&format!(
+            ".default_value(\"{}\")",
+            default.replace('"', "\\\"")
+        ));
+    }
+    if !flag.choices.is_empty() {
+        calls.push_str(&format!(
+            ".value_parser([{}])",
+            flag.choices
+                .iter()
+                .map(|choice| format!("\"{}\"", choice.replace('"', "\\\"")))
+                .collect::<Vec<_>>()
+                .join(", ")
+        ));
+    }
+    calls
+}
+
 /// Build `cli.rs` using clap's builder API
 fn generate_cli_builder(spec: &CliSpec, keep_help: bool, keep_verbose: bool) -> String {
     let mut cli_file_contents_string = String::new();
@@ -166,6 +290,13 @@ fn generate_cli_builder(spec: &CliSpec, keep_help: bool, keep_verbose: bool) ->
         spec.description.replace('"', "\\\"")
     ));
 
+    if !spec.arguments.is_empty() {
+        cli_file_contents_string.push_str(&format!(
+            "    cmd = cmd{};\n",
+            argument_calls(&spec.arguments)
+        ));
+    }
+
     // Optionally disable auto-help
     if !keep_help {
         cli_file_contents_string.push_str("    cmd = cmd.disable_help_subcommand(true);\n");
@@ -174,6 +305,10 @@ fn generate_cli_builder(spec: &CliSpec, keep_help: bool, keep_verbose: bool) ->
 
     // Global flags
     for flag in &spec.children.flags {
+        // Inherited flags are generated once, as global args on the defining command
+        if flag.inherited {
+            continue;
+        }
         let name = flag.long.as_deref().unwrap_or_else(|| {
             flag.short
                 .as_deref()
@@ -201,13 +336,15 @@ fn generate_cli_builder(spec: &CliSpec, keep_help: bool, keep_verbose: bool) ->
             _ => "ArgAction::Count",
         };
         cli_file_contents_string.push_str(&format!(
-            "    cmd = cmd.arg(Arg::new(\"{key}\"){short}{long}.help(\"{help}\").action({action}).required({required}));\n",
+            "    cmd = cmd.arg(Arg::new(\"{key}\"){short}{long}.help(\"{help}\"){values}.action({action}).required({required}){global});\n",
             key = key,
             short = short_call,
             long = long_call,
             help = help,
             action = action,
-            required = required
+            required = required,
+            global = if flag.persistent { ".global(true)" } else { "" },
+            values = value_calls(flag, action)
         ));
     }
 
@@ -215,9 +352,11 @@ fn generate_cli_builder(spec: &CliSpec, keep_help: bool, keep_verbose: bool) ->
     cli_file_contents_string.push_str("\n    cmd = cmd.subcommands(vec![\n");
     for cmd_spec in spec.children.commands.values() {
         let mut builder = format!(
-            "Command::new(\"{}\").about(\"{}\")",
+            "Command::new(\"{}\").about(\"{}\"){}{}",
             cmd_spec.name,
-            cmd_spec.description.replace('"', "\\\"")
+            cmd_spec.description.replace('"', "\\\""),
+            aliases_and_examples_calls(cmd_spec),
+            argument_calls(&cmd_spec.arguments)
         );
         // if !keep_help {
         //     builder.push_str(".disable_help_flag(true)");
@@ -225,6 +364,9 @@ fn generate_cli_builder(spec: &CliSpec, keep_help: bool, keep_verbose: bool) ->
 
         // Flags for this command
         for flag in &cmd_spec.children.flags {
+            if flag.inherited {
+                continue;
+            }
             let name = flag.long.as_deref().unwrap_or_else(|| {
                 flag.short
                     .as_deref()
@@ -252,13 +394,15 @@ fn generate_cli_builder(spec: &CliSpec, keep_help: bool, keep_verbose: bool) ->
                 _ => "ArgAction::Count",
             };
             builder.push_str(&format!(
-                ".arg(Arg::new(\"{key}\"){short}{long}.help(\"{help}\").action({action}).required({required}))",
+                ".arg(Arg::new(\"{key}\"){short}{long}.help(\"{help}\"){values}.action({action}).required({required}){global})",
                 key = key,
                 short = short_call,
                 long = long_call,
                 help = help,
                 action = action,
-                required = required
+                required = required,
+                global = if flag.persistent { ".global(true)" } else { "" },
+                values = value_calls(flag, action)
             ));
         }
 
@@ -267,12 +411,17 @@ fn generate_cli_builder(spec: &CliSpec, keep_help: bool, keep_verbose: bool) ->
             builder.push_str(".subcommands(vec![");
             for sub in cmd_spec.children.commands.values() {
                 let mut sub_b = format!(
-                    "Command::new(\"{}\").about(\"{}\")",
+                    "Command::new(\"{}\").about(\"{}\"){}{}",
                     sub.name,
-                    sub.description.replace('"', "\\\"")
+                    sub.description.replace('"', "\\\""),
+                    aliases_and_examples_calls(sub),
+                    argument_calls(&sub.arguments)
                 );
 
                 for flag in &sub.children.flags {
+                    if flag.inherited {
+                        continue;
+                    }
                     let name = flag.long.as_deref().unwrap_or_else(|| {
                         flag.short
                             .as_deref()
@@ -300,13 +449,15 @@ fn generate_cli_builder(spec: &CliSpec, keep_help: bool, keep_verbose: bool) ->
                         _ => "ArgAction::Count",
                     };
                     sub_b.push_str(&format!(
-                        ".arg(Arg::new(\"{key}\"){short}{long}.help(\"{help}\").action({action}).required({required}))",
+                        ".arg(Arg::new(\"{key}\"){short}{long}.help(\"{help}\"){values}.action({action}).required({required}){global})",
                         key = key,
                         short = short_call,
                         long = long_call,
                         help = help,
                         action = action,
-                        required = required
+                        required = required,
+                        global = if flag.persistent { ".global(true)" } else { "" },
+                        values = value_calls(flag, action)
                     ));
                 }
                 builder.push_str(&format!("{},", sub_b));
@@ -336,6 +487,9 @@ fn generate_main_builder(spec: &CliSpec, keep_help: bool, keep_verbose: bool) ->
 
         // extract this command's flags
         for flag in &cmd_spec.children.flags {
+            if flag.inherited {
+                continue;
+            }
             let key = flag.long.as_deref().unwrap_or_else(|| {
                 flag.short
                     .as_deref()
@@ -379,6 +533,9 @@ fn generate_main_builder(spec: &CliSpec, keep_help: bool, keep_verbose: bool) ->
                     sub.name
                 ));
                 for flag in &sub.children.flags {
+                    if flag.inherited {
+                        continue;
+                    }
                     let key = flag.long.as_deref().unwrap_or_else(|| {
                         flag.short
                             .as_deref()
@@ -0,0 +1,359 @@
+use serde_json::{Value, json};
+use std::env::current_dir;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::cli_parser::crawled_commands;
+use crate::comparison::ChangeType;
+use crate::models::{LintReport, ReportFormat, ReportResult, Severity, SourceLocation};
+
+const SARIF_SCHEMA: &str = "https://json.schemastore.org/sarif-2.1.0.json";
+/// What relative help page paths in a SARIF log are relative to: the directory
+/// `clint` ran in
+const SARIF_BASE_ID: &str = "SRCROOT";
+
+fn find_node<'a>(structure: &'a Value, command_path: &str) -> Option<&'a Value> {
+    let root_path = structure["command_path"].as_str()?;
+    let relative_path = command_path.strip_prefix(root_path)?;
+    if !relative_path.is_empty() && !relative_path.starts_with(' ') {
+        return None;
+    }
+    relative_path
+        .split_whitespace()
+        .try_fold(structure, |node, name| {
+            node.get("children")?.get("COMMAND")?.get(name)
+        })
+}
+
+/// Line number of the line defining the flag, or else the first one naming
+/// it, e.g. in the usage line
+fn flag_line(help_text: &str, flag: &str) -> Option<usize> {
+    let names_flag = |line: &str| {
+        line.split(|c: char| c.is_whitespace() || matches!(c, ',' | '=' | '[' | ']'))
+            .any(|word| word == flag)
+    };
+    let lines: Vec<&str> = help_text.lines().collect();
+    lines
+        .iter()
+        .position(|line| line.trim_start().starts_with('-') && names_flag(line))
+        .or_else(|| lines.iter().position(|line| names_flag(line)))
+        .map(|index| index + 1)
+}
+
+/// The captured help page a command was parsed from, and the line its flag
+/// is on. Structures crawled by running the program have no location.
+fn source_location(
+    structure: &Value,
+    command_path: &str,
+    flag: Option<&str>,
+) -> Option<SourceLocation> {
+    let help_source = &structure["metadata"]["help_source"];
+    let file = if let Some(dir) = help_source["dir"].as_str() {
+        PathBuf::from(dir).join(format!("{}.txt", command_path))
+    } else if let Some(file) = help_source["file"].as_str()
+        && structure["command_path"].as_str() == Some(command_path)
+    {
+        PathBuf::from(file)
+    } else {
+        return None;
+    };
+
+    // The file may have moved since the parse, so fall back to the saved page
+    let help_text = fs::read_to_string(&file).ok().or_else(|| {
+        find_node(structure, command_path)?["outputs"]["help_page"]["stdout"]
+            .as_str()
+            .map(String::from)
+    });
+    // Flags print as `-s/--long` in comparisons
+    let line = flag
+        .and_then(|flag| flag.rsplit('/').next())
+        .zip(help_text)
+        .and_then(|(flag, help_text)| flag_line(&help_text, flag));
+    Some(SourceLocation {
+        file: file.display().to_string(),
+        line,
+    })
+}
+
+pub fn lint_results(report: &LintReport, structure: &Value) -> Vec<ReportResult> {
+    report
+        .findings
+        .iter()
+        .map(|finding| ReportResult {
+            rule_id: finding.rule.clone(),
+            severity: finding.severity,
+            command_path: finding.command_path.clone(),
+            flag: finding.flag.clone(),
+            message: finding.message.clone(),
+            location: source_location(structure, &finding.command_path, finding.flag.as_deref()),
+        })
+        .collect()
+}
+
+/// Removed things are located in the old parse, everything else in the new one
+pub fn change_results(changes: &[ChangeType], from: &Value, to: &Value) -> Vec<ReportResult> {
+    let root_name = to["name"].as_str().unwrap_or_default();
+    changes
+        .iter()
+        .map(|change| {
+            let command_path = change.command_path(root_name);
+            let structure = if change.is_removal() { from } else { to };
+            ReportResult {
+                rule_id: change.rule_id().to_string(),
+                severity: change.severity(),
+                location: source_location(structure, &command_path, change.flag()),
+                command_path,
+                flag: change.flag().map(String::from),
+                // Some changes span several lines in the terminal
+                message: change
+                    .format()
+                    .lines()
+                    .map(str::trim)
+                    .collect::<Vec<_>>()
+                    .join(" "),
+            }
+        })
+        .collect()
+}
+
+fn sarif_level(severity: Severity) -> &'static str {
+    match severity {
+        Severity::Error => "error",
+        Severity::Warning => "warning",
+        Severity::Info | Severity::Off => "note",
+    }
+}
+
+/// A path as a URI reference, with everything but unreserved characters and
+/// separators percent-encoded, e.g. `pages/kubectl%20get.txt`
+fn uri_path(path: &Path) -> String {
+    let mut uri = String::new();
+    for byte in path.to_string_lossy().bytes() {
+        match byte {
+            b if b.is_ascii_alphanumeric() || b"-._~/".contains(&b) => uri.push(b as char),
+            b => uri.push_str(&format!("%{:02X}", b)),
+        }
+    }
+    uri
+}
+
+/// A `file://` URI for an absolute path, or a relative one resolved against
+/// [`SARIF_BASE_ID`]
+fn artifact_location(file: &str) -> Value {
+    let path = Path::new(file);
+    if path.is_absolute() {
+        json!({ "uri": format!("file://{}", uri_path(path)) })
+    } else {
+        json!({
+            "uri": uri_path(path.strip_prefix("./").unwrap_or(path)),
+            "uriBaseId": SARIF_BASE_ID,
+        })
+    }
+}
+
+/// A SARIF 2.1.0 log with one run. Results are keyed by command path as a
+/// logical location, plus the help page file and line when known.
+pub fn to_sarif(results: &[ReportResult], rule_descriptions: &[(&str, &str)]) -> Value {
+    let mut rule_ids: Vec<&str> = results.iter().map(|r| r.rule_id.as_str()).collect();
+    rule_ids.sort();
+    rule_ids.dedup();
+    let rules: Vec<Value> = rule_ids
+        .iter()
+        .map(|id| {
+            let mut rule = json!({ "id": id });
+            if let Some((_, description)) = rule_descriptions.iter().find(|(rule, _)| rule == id) {
+                rule["shortDescription"] = json!({ "text": description });
+            }
+            rule
+        })
+        .collect();
+
+    let sarif_results: Vec<Value> = results
+        .iter()
+        .map(|result| {
+            let (name, kind) = match &result.flag {
+                Some(flag) => (format!("{} {}", result.command_path, flag), "member"),
+                None => (result.command_path.clone(), "function"),
+            };
+            let mut location = json!({
+                "logicalLocations": [{ "fullyQualifiedName": name, "kind": kind }],
+            });
+            if let Some(source) = &result.location {
+                location["physicalLocation"] = json!({
+                    "artifactLocation": artifact_location(&source.file),
+                });
+                if let Some(line) = source.line {
+                    location["physicalLocation"]["region"] = json!({ "startLine": line });
+                }
+            }
+            json!({
+                "ruleId": result.rule_id,
+                "level": sarif_level(result.severity),
+                "message": { "text": result.message },
+                "locations": [location],
+            })
+        })
+        .collect();
+
+    let mut run = json!({
+        "tool": {
+            "driver": {
+                "name": "clint",
+                "version": env!("CARGO_PKG_VERSION"),
+                "rules": rules,
+            }
+        },
+        "results": sarif_results,
+    });
+    if let Ok(dir) = current_dir() {
+        run["originalUriBaseIds"] = json!({
+            SARIF_BASE_ID: { "uri": format!("file://{}/", uri_path(&dir).trim_end_matches('/')) },
+        });
+    }
+
+    json!({
+        "version": "2.1.0",
+        "$schema": SARIF_SCHEMA,
+        "runs": [run],
+    })
+}
+
+/// Whether XML 1.0 allows a character at all, even escaped. Most control
+/// characters, such as the `\x1b` of a color code, aren't allowed.
+fn is_xml_char(c: char) -> bool {
+    matches!(c, '\t' | '\n' | '\r' | ' '..='\u{d7ff}' | '\u{e000}'..='\u{fffd}') || c >= '\u{10000}'
+}
+
+fn xml_escape(text: &str) -> String {
+    text.chars()
+        .filter(|&c| is_xml_char(c))
+        .collect::<String>()
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+/// A JUnit XML suite with a test case per command. Warnings and errors fail
+/// the command's test case, info results are attached as output.
+pub fn to_junit(suite_name: &str, structure: &Value, results: &[ReportResult]) -> String {
+    let mut command_paths: Vec<String> = crawled_commands(structure)
+        .into_iter()
+        .map(|(command_path, _)| command_path)
+        .collect();
+    // Removed commands are only in the results
+    for result in results {
+        if !command_paths.contains(&result.command_path) {
+            command_paths.push(result.command_path.clone());
+        }
+    }
+    let program = structure["name"].as_str().unwrap_or_default();
+    let is_failure = |result: &&ReportResult| result.severity.reaches(Severity::Warning);
+    let failures = command_paths
+        .iter()
+        .filter(|path| {
+            results
+                .iter()
+                .filter(is_failure)
+                .any(|result| &result.command_path == *path)
+        })
+        .count();
+
+    let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    xml.push_str(&format!(
+        "<testsuites name=\"{}\" tests=\"{}\" failures=\"{}\">\n",
+        xml_escape(suite_name),
+        command_paths.len(),
+        failures
+    ));
+    xml.push_str(&format!(
+        "  <testsuite name=\"{}\" tests=\"{}\" failures=\"{}\">\n",
+        xml_escape(suite_name),
+        command_paths.len(),
+        failures
+    ));
+    for command_path in &command_paths {
+        let command_results: Vec<&ReportResult> = results
+            .iter()
+            .filter(|result| &result.command_path == command_path)
+            .collect();
+        let file = command_results
+            .iter()
+            .find_map(|result| result.location.as_ref())
+            .map(|location| format!(" file=\"{}\"", xml_escape(&location.file)))
+            .unwrap_or_default();
+        if command_results.is_empty() {
+            xml.push_str(&format!(
+                "    <testcase classname=\"{}\" name=\"{}\"{}/>\n",
+                xml_escape(program),
+                xml_escape(command_path),
+                file
+            ));
+            continue;
+        }
+        xml.push_str(&format!(
+            "    <testcase classname=\"{}\" name=\"{}\"{}>\n",
+            xml_escape(program),
+            xml_escape(command_path),
+            file
+        ));
+        let mut notes = Vec::new();
+        for result in command_results {
+            let location = result
+                .location
+                .as_ref()
+                .map(|location| match location.line {
+                    Some(line) => format!(" ({}:{})", location.file, line),
+                    None => format!(" ({})", location.file),
+                })
+                .unwrap_or_default();
+            let text = format!(
+                "{}: [{}] {}{}",
+                sarif_level(result.severity),
+                result.rule_id,
+                result.message,
+                location
+            );
+            if is_failure(&result) {
+                xml.push_str(&format!(
+                    "      <failure type=\"{}\" message=\"{}\">{}</failure>\n",
+                    xml_escape(&result.rule_id),
+                    xml_escape(&result.message),
+                    xml_escape(&text)
+                ));
+            } else {
+                notes.push(text);
+            }
+        }
+        if !notes.is_empty() {
+            xml.push_str(&format!(
+                "      <system-out>{}</system-out>\n",
+                xml_escape(&notes.join("\n"))
+            ));
+        }
+        xml.push_str("    </testcase>\n");
+    }
+    xml.push_str("  </testsuite>\n</testsuites>\n");
+    xml
+}
+
+/// Writes results in a report format, returning what went wrong if it failed
+pub fn write_report(
+    path: &PathBuf,
+    format: ReportFormat,
+    suite_name: &str,
+    structure: &Value,
+    results: &[ReportResult],
+    rule_descriptions: &[(&str, &str)],
+) -> Result<(), String> {
+    let content = match format {
+        ReportFormat::Sarif => serde_json::to_string_pretty(&to_sarif(results, rule_descriptions))
+            .map_err(|e| e.to_string())?,
+        ReportFormat::Junit => to_junit(suite_name, structure, results),
+    };
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+    fs::write(path, content).map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+}
@@ -26,6 +26,12 @@ export interface CommandFlag {
   description: string;
   required: boolean;
   defaultValue?: string;
+  choices?: string[];
+  env?: string[];
+  deprecated?: boolean;
+  negates?: string;
+  repeatable?: boolean;
+  valuePlaceholder?: string;
   formats: CommandFlagFormat[];
 }
 
@@ -40,10 +46,18 @@ export interface CommandArgumentFormat {
 export interface CommandArgument {
   description: string;
   required: boolean;
+  variadic?: boolean;
   valueDataType: CommandComponentDataType;
   formats: CommandArgumentFormat[];
 }
 
+// Parse confidence, from the weights of the evidence an entry was recognised by
+export const ConfidenceSchema = z.number().min(0).max(1);
+export const EvidenceSchema = z.array(z.string()).describe("e.g. 'header_matched', 'column_aligned', 'in_usage'");
+
+// Names of the crawled variants an entry was found in
+export const VariantsSchema = z.array(z.string());
+
 // Component type enum for usage parsing
 export const ComponentTypeSchema = z.enum([
   'Flag',
@@ -51,9 +65,17 @@ export const ComponentTypeSchema = z.enum([
   'Keyword',
   'Group',
   'AlternativeGroup',
-  'KeyValuePair'
+  'KeyValuePair',
+  'OptionsShortcut',
+  'Separator'
 ]);
 
+// Byte range within the usage line
+export const SpanSchema = z.object({
+  start: z.number().int().min(0),
+  end: z.number().int().min(0)
+});
+
 // Usage component schema with recursive structure
 export const UsageComponentSchema: z.ZodType<any> = z.lazy(() => z.object({
   component_type: ComponentTypeSchema,
@@ -62,7 +84,9 @@ export const UsageComponentSchema: z.ZodType<any> = z.lazy(() => z.object({
   repeatable: z.boolean(),
   key_value: z.boolean(),
   alternatives: z.array(UsageComponentSchema),
-  children: z.array(UsageComponentSchema)
+  children: z.array(UsageComponentSchema),
+  value: z.string().nullable().optional().describe("Value placeholder bound to a flag, e.g. '<file>' in '--output <file>'"),
+  span: SpanSchema.optional()
 }));
 
 // Outputs schema for command execution results
@@ -76,15 +100,58 @@ export const FlagSchema = z.object({
   short: z.string().nullable(),
   long: z.string().nullable(), 
   data_type: z.string().nullable(),
+  pflag_type: z.string().nullable().optional().describe("Value type printed by pflag (Cobra), e.g. 'string' or 'stringArray'"),
   description: z.string().nullable(),
-  parent_header: z.string()
+  parent_header: z.string(),
+  inherited: z.boolean().optional().describe("Listed under 'Global Flags:', i.e. defined by an ancestor command"),
+  persistent: z.boolean().optional().describe('Available to every subcommand of the command that defines it'),
+  defined_in: z.string().nullable().optional().describe('Command path of the ancestor defining an inherited flag'),
+  default: z.string().nullable().optional(),
+  choices: z.array(z.string()).optional().describe("Accepted values, e.g. from 'one of: json|yaml'"),
+  env: z.array(z.string()).optional().describe('Environment variables the flag can be set from'),
+  deprecated: z.boolean().optional(),
+  negates: z.string().nullable().optional().describe("For '--no-x' flags, the flag being negated ('--x')"),
+  repeatable: z.boolean().optional(),
+  value_placeholder: z.string().nullable().optional().describe("Value name printed after the flag, e.g. 'string' or 'FILE'"),
+  confidence: ConfidenceSchema.optional(),
+  evidence: EvidenceSchema.optional(),
+  variants: VariantsSchema.optional()
 });
 
 // Usage schema
 export const UsageSchema = z.object({
   usage_string: z.string(),
   parent_header: z.string(),
-  components: z.array(UsageComponentSchema).optional()
+  components: z.array(UsageComponentSchema).optional(),
+  parse_errors: z.array(z.object({
+    message: z.string(),
+    span: SpanSchema
+  })).optional()
+});
+
+// Example schema pairing '# comment' lines with the command that follows
+export const ExampleSchema = z.object({
+  comment: z.string().nullable(),
+  command_line: z.string()
+});
+
+// Positional argument schema, ordered by position on the command line
+export const ArgumentSchema = z.object({
+  name: z.string(),
+  position: z.number().int().min(0),
+  required: z.boolean(),
+  variadic: z.boolean().describe("Accepts any number of values, e.g. 'FILE...'"),
+  description: z.string().nullable(),
+  confidence: ConfidenceSchema.optional(),
+  evidence: EvidenceSchema.optional()
+});
+
+// Help topic schema for the 'Additional help topics:' section
+export const HelpTopicSchema = z.object({
+  name: z.string(),
+  description: z.string(),
+  command_path: z.string(),
+  parent_header: z.string()
 });
 
 // Other schema for miscellaneous lines
@@ -93,11 +160,72 @@ export const OtherSchema = z.object({
   parent_header: z.string()
 });
 
+// Environment the parsed program was run with while crawling
+export const CrawlEnvironmentSchema = z.object({
+  vars: z.record(z.string(), z.string()),
+  working_dir: z.string().nullable(),
+  exec_prefix: z.string().nullable().optional().describe('Template of the wrapper command the program was run through')
+});
+
+// What happened while crawling a single command
+export const NodeReportSchema = z.object({
+  command_path: z.string(),
+  invocation: z.string(),
+  duration_ms: z.number().int().min(0),
+  status: z.number().int(),
+  stderr_excerpt: z.string().nullable().optional(),
+  reused: z.boolean().optional().describe('Taken unchanged from the previous parse'),
+  unclassified_lines: z.array(z.string()).optional(),
+  warnings: z.array(z.string()).optional(),
+  variant: z.string().nullable().optional().describe('Variant the command was crawled in')
+});
+
+export const CrawlReportSchema = z.object({
+  nodes: z.array(NodeReportSchema)
+});
+
+// Startup latency of a command's --help, from 'clint parse --profile'
+export const CommandLatencySchema = z.object({
+  command_path: z.string(),
+  median_ms: z.number().min(0),
+  p95_ms: z.number().min(0),
+  samples_ms: z.array(z.number()),
+  outlier: z.boolean().describe('Much slower than its sibling commands'),
+  sibling_median_ms: z.number().nullable().optional()
+});
+
+export const LatencyProfileSchema = z.object({
+  runs: z.number().int().min(0),
+  commands: z.array(CommandLatencySchema)
+});
+
+// Environment variant crawled with --variant
+export const VariantSchema = z.object({
+  name: z.string(),
+  env: z.record(z.string(), z.string())
+});
+
+// Captured help pages the structure was parsed from, with 'clint parse --from-dir' or '--from-text'
+export const HelpSourceSchema = z.object({
+  dir: z.string().optional(),
+  file: z.string().optional()
+});
+
+// Parse metadata attached to the root of the structure
+export const MetadataSchema = z.object({
+  environment: CrawlEnvironmentSchema.optional(),
+  crawl_report: CrawlReportSchema.optional(),
+  help_source: HelpSourceSchema.optional(),
+  latency: LatencyProfileSchema.optional(),
+  variants: z.array(VariantSchema).optional().describe('Variants merged into the structure, in crawl order')
+}).passthrough();
+
 // Children schema with recursive command structure
 export const ChildrenSchema: z.ZodType<any> = z.lazy(() => z.object({
   COMMAND: z.record(z.string(), CommandSchema),
   FLAG: z.array(FlagSchema),
   USAGE: z.array(UsageSchema),
+  HELP_TOPIC: z.array(HelpTopicSchema).optional(),
   OTHER: z.array(OtherSchema)
 }));
 
@@ -110,6 +238,14 @@ export const CommandSchema: z.ZodType<any> = z.lazy(() => z.object({
   depth: z.number().int().min(0).optional().describe('Nesting depth of the command (0 for root, 1 for first level, etc.)'),
   command_path: z.string().optional().describe('Full command path (e.g., "my_cli open socket all")'),
   outputs: z.record(z.string(), OutputsSchema).optional(),
+  aliases: z.array(z.string()).optional().describe("Alternative names listed in the 'Aliases:' section"),
+  examples: z.array(ExampleSchema).optional(),
+  arguments: z.array(ArgumentSchema).optional().describe('Positional arguments, in the order they are given on the command line'),
+  confidence: ConfidenceSchema.optional(),
+  evidence: EvidenceSchema.optional(),
+  variants: VariantsSchema.optional(),
+  external_plugin: z.boolean().optional().describe("Found as a '<prog>-<name>' executable rather than in a help page"),
+  plugin_command: z.string().optional().describe('Executable run to crawl an external plugin'),
   children: ChildrenSchema
 }));
 
@@ -118,18 +254,33 @@ export const CLIStructureSchema = z.object({
   name: z.string(),
   description: z.string(),
   version: z.string(),
+  aliases: z.array(z.string()).optional(),
+  examples: z.array(ExampleSchema).optional(),
+  arguments: z.array(ArgumentSchema).optional(),
   depth: z.number().int().min(0).optional(),
   command_path: z.string().optional(),
+  metadata: MetadataSchema.optional(),
   children: ChildrenSchema
 });
 
 // Type exports for TypeScript usage
 export type ComponentType = z.infer<typeof ComponentTypeSchema>;
+export type Span = z.infer<typeof SpanSchema>;
 export type UsageComponent = z.infer<typeof UsageComponentSchema>;
 export type Outputs = z.infer<typeof OutputsSchema>;
 export type Flag = z.infer<typeof FlagSchema>;
 export type Usage = z.infer<typeof UsageSchema>;
+export type Example = z.infer<typeof ExampleSchema>;
+export type Argument = z.infer<typeof ArgumentSchema>;
+export type HelpTopic = z.infer<typeof HelpTopicSchema>;
 export type Other = z.infer<typeof OtherSchema>;
+export type NodeReport = z.infer<typeof NodeReportSchema>;
+export type CrawlReport = z.infer<typeof CrawlReportSchema>;
+export type Variant = z.infer<typeof VariantSchema>;
+export type CommandLatency = z.infer<typeof CommandLatencySchema>;
+export type LatencyProfile = z.infer<typeof LatencyProfileSchema>;
+export type HelpSource = z.infer<typeof HelpSourceSchema>;
+export type Metadata = z.infer<typeof MetadataSchema>;
 export type Children = z.infer<typeof ChildrenSchema>;
 export type Command = z.infer<typeof CommandSchema>;
 export type CLIStructure = z.infer<typeof CLIStructureSchema>;
@@ -3,10 +3,18 @@ use std::collections::HashSet;
 use std::fs;
 use std::path::PathBuf;
 
-use crate::models::{CLIKeywords, CLISummary};
+use crate::confidence::filter_by_confidence;
+use crate::models::{CLIKeywords, CLISummary, LineFlag};
 
-pub fn generate_summary(path: &PathBuf) -> Result<CLISummary, Box<dyn std::error::Error>> {
-    let data = match extract_data(path) {
+pub fn generate_summary(
+    path: &PathBuf,
+    min_confidence: Option<f64>,
+) -> Result<CLISummary, Box<dyn std::error::Error>> {
+    let mut json = read_json(path);
+    if let Some(min_confidence) = min_confidence {
+        filter_by_confidence(&mut json, min_confidence);
+    }
+    let data = match extract_data(&json) {
         Some(data) => data,
         None => {
             return Err("Failed to extract data from JSON".into());
@@ -34,6 +42,18 @@ pub fn generate_summary(path: &PathBuf) -> Result<CLISummary, Box<dyn std::error
         + unique_short_flag_count
         + unique_long_flag_count;
 
+    let mut total_alias_count = 0;
+    let mut total_example_count = 0;
+    let mut total_help_topic_count = 0;
+    let mut total_argument_count = 0;
+    count_documentation_entries(
+        &json,
+        &mut total_alias_count,
+        &mut total_example_count,
+        &mut total_help_topic_count,
+        &mut total_argument_count,
+    );
+
     Ok(CLISummary {
         unique_keywords_count,
         unique_command_count,
@@ -44,12 +64,59 @@ pub fn generate_summary(path: &PathBuf) -> Result<CLISummary, Box<dyn std::error
         total_subcommand_count,
         total_short_flag_count,
         total_long_flag_count,
+        total_alias_count,
+        total_example_count,
+        total_help_topic_count,
+        total_argument_count,
     })
 }
 
-fn extract_data(path: &PathBuf) -> Option<CLIKeywords> {
+fn read_json(path: &PathBuf) -> Value {
     let raw = fs::read_to_string(path).expect("Failed to read file");
-    let json: Value = serde_json::from_str(&raw).expect("Failed to read file as JSON");
+    serde_json::from_str(&raw).expect("Failed to read file as JSON")
+}
+
+fn count_documentation_entries(
+    node: &Value,
+    alias_count: &mut usize,
+    example_count: &mut usize,
+    help_topic_count: &mut usize,
+    argument_count: &mut usize,
+) {
+    *alias_count += node
+        .get("aliases")
+        .and_then(|v| v.as_array())
+        .map_or(0, |a| a.len());
+    *example_count += node
+        .get("examples")
+        .and_then(|v| v.as_array())
+        .map_or(0, |e| e.len());
+    *argument_count += node
+        .get("arguments")
+        .and_then(|v| v.as_array())
+        .map_or(0, |a| a.len());
+
+    if let Some(children) = node.get("children") {
+        *help_topic_count += children
+            .get("HELP_TOPIC")
+            .and_then(|v| v.as_array())
+            .map_or(0, |t| t.len());
+
+        if let Some(command_map) = children.get("COMMAND").and_then(|v| v.as_object()) {
+            for cmd_obj in command_map.values() {
+                count_documentation_entries(
+                    cmd_obj,
+                    alias_count,
+                    example_count,
+                    help_topic_count,
+                    argument_count,
+                );
+            }
+        }
+    }
+}
+
+fn extract_data(json: &Value) -> Option<CLIKeywords> {
     let base_program = json
         .get("name")
         .and_then(|v| v.as_str())
@@ -61,6 +128,10 @@ fn extract_data(path: &PathBuf) -> Option<CLIKeywords> {
     let mut short_flags = vec![];
     let mut long_flags = vec![];
 
+    if let Some(children) = json.get("children") {
+        collect_local_flags(children, &mut short_flags, &mut long_flags);
+    }
+
     if let Some(children) = json.get("children")
         && let Some(command_map) = children.get("COMMAND").and_then(|v| v.as_object())
     {
@@ -114,8 +185,14 @@ fn walk_commands_recursively(
         }
     }
 
+    collect_local_flags(node, short_flags, long_flags);
+}
+
+/// Collects the flags a command defines itself. Inherited flags are skipped so
+/// that a global flag is only counted once, on the command that defines it.
+fn collect_local_flags(node: &Value, short_flags: &mut Vec<String>, long_flags: &mut Vec<String>) {
     if let Some(flags) = node.get("FLAG").and_then(|v| v.as_array()) {
-        for flag in flags {
+        for flag in flags.iter().filter(|flag| !LineFlag::is_inherited(flag)) {
             if let Some(s) = flag.get("short").and_then(|v| v.as_str()) {
                 short_flags.push(s.to_string());
             }
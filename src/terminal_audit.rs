@@ -0,0 +1,156 @@
+use serde_json::Value;
+
+use crate::cli_parser::{crawled_commands, run_process, strip_ansi_codes};
+use crate::models::{
+    CommandTerminalFindings, CrawlEnvironment, TerminalAudit, TerminalCheck, TerminalFinding,
+};
+
+/// Terminal widths the help pages are printed at
+pub const AUDIT_WIDTHS: [u16; 3] = [40, 80, 200];
+/// How many offending lines a finding keeps
+const MAX_OFFENDING_LINES: usize = 5;
+/// A terminal that supports colour, so only `NO_COLOR` or the missing TTY is
+/// left to keep escape codes out
+const COLOR_TERM: &str = "xterm-256color";
+
+fn with_env(environment: &CrawlEnvironment, vars: &[(&str, &str)]) -> CrawlEnvironment {
+    let mut environment = environment.clone();
+    environment
+        .extra_env
+        .extend(vars.iter().map(|(k, v)| (k.to_string(), v.to_string())));
+    environment
+}
+
+/// Offending lines with their line numbers, escape characters made visible
+fn offending_lines<'a>(lines: impl Iterator<Item = (usize, &'a str)>) -> (usize, Vec<String>) {
+    let lines: Vec<String> = lines
+        .map(|(number, line)| format!("{}: {}", number + 1, line.replace('\x1b', "\\x1b")))
+        .collect();
+    let count = lines.len();
+    (count, lines.into_iter().take(MAX_OFFENDING_LINES).collect())
+}
+
+fn long_lines_finding(
+    check: TerminalCheck,
+    environment: String,
+    output: &str,
+    limit: usize,
+    limit_name: &str,
+) -> Option<TerminalFinding> {
+    let (count, lines) = offending_lines(
+        output
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| line.chars().count() > limit),
+    );
+    (count > 0).then(|| TerminalFinding {
+        check,
+        environment,
+        message: format!("{} lines longer than {} {}", count, limit, limit_name),
+        lines,
+    })
+}
+
+fn escape_codes_finding(
+    check: TerminalCheck,
+    environment: String,
+    output: &str,
+) -> Option<TerminalFinding> {
+    let (count, lines) = offending_lines(
+        output
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| line.contains('\x1b')),
+    );
+    (count > 0).then(|| TerminalFinding {
+        check,
+        environment,
+        message: format!("{} lines with escape codes", count),
+        lines,
+    })
+}
+
+fn help_output(command: &str, environment: &CrawlEnvironment) -> String {
+    let argv: Vec<String> = format!("{} --help", command)
+        .split_whitespace()
+        .map(String::from)
+        .collect();
+    let invocation = run_process(&argv, environment);
+    format!("{}{}", invocation.stdout, invocation.stderr)
+}
+
+fn audit_command(
+    command: &str,
+    environment: &CrawlEnvironment,
+    max_line_length: usize,
+) -> Vec<TerminalFinding> {
+    let mut findings = Vec::new();
+
+    for columns in AUDIT_WIDTHS {
+        let mut width_environment = environment.clone();
+        width_environment.columns = columns;
+        let output = strip_ansi_codes(&help_output(command, &width_environment));
+        findings.extend(long_lines_finding(
+            TerminalCheck::Width,
+            format!("COLUMNS={}", columns),
+            &output,
+            columns as usize,
+            "columns",
+        ));
+        // The budget holds however wide the terminal is
+        if columns == AUDIT_WIDTHS[AUDIT_WIDTHS.len() - 1] {
+            findings.extend(long_lines_finding(
+                TerminalCheck::LineLength,
+                format!("COLUMNS={}", columns),
+                &output,
+                max_line_length,
+                "characters",
+            ));
+        }
+    }
+
+    let no_color = [("NO_COLOR", "1"), ("TERM", COLOR_TERM)];
+    let output = help_output(command, &with_env(environment, &no_color));
+    findings.extend(escape_codes_finding(
+        TerminalCheck::NoColor,
+        format!("NO_COLOR=1 TERM={}", COLOR_TERM),
+        &output,
+    ));
+
+    // An empty NO_COLOR counts as unset
+    let non_tty = [("NO_COLOR", ""), ("TERM", COLOR_TERM)];
+    let output = help_output(command, &with_env(environment, &non_tty));
+    findings.extend(escape_codes_finding(
+        TerminalCheck::NonTty,
+        format!("NO_COLOR= TERM={}", COLOR_TERM),
+        &output,
+    ));
+
+    findings
+}
+
+/// Prints every command's help page at several terminal widths and with
+/// colour-capable terminals, and reports long lines and escape codes.
+/// Output is always captured through a pipe, so the program never sees a TTY.
+pub fn audit_terminal(
+    structure: &Value,
+    environment: &CrawlEnvironment,
+    max_line_length: usize,
+) -> TerminalAudit {
+    let commands = crawled_commands(structure)
+        .into_iter()
+        .map(|(command_path, command)| CommandTerminalFindings {
+            command_path,
+            findings: audit_command(&command, environment, max_line_length),
+        })
+        .collect();
+    TerminalAudit {
+        program: structure["name"].as_str().unwrap_or_default().to_string(),
+        version: structure["version"]
+            .as_str()
+            .unwrap_or_default()
+            .to_string(),
+        max_line_length,
+        commands,
+    }
+}
@@ -0,0 +1,205 @@
+use serde_json::Value;
+
+use crate::cli_parser::{WalkedCommand, execute_full_command, walk_commands};
+use crate::models::{
+    CrawlOptions, TypoCoverage, TypoKind, TypoOutcome, TypoProbeReport, TypoProbeResult,
+};
+
+/// Phrases programs introduce a suggested correction with, e.g. Cobra's "Did
+/// you mean this?" or clap's "a similar argument exists"
+const SUGGESTION_HINTS: [&str; 4] = [
+    "did you mean",
+    "similar",
+    "perhaps you meant",
+    "maybe you meant",
+];
+
+/// One typo of each kind that applies to a name
+pub fn derive_typos(name: &str) -> Vec<(TypoKind, String)> {
+    let chars: Vec<char> = name.chars().collect();
+    let len = chars.len();
+    let mut typos = Vec::new();
+
+    // Swap two different neighbouring letters, starting from the middle
+    if len >= 2
+        && let Some(i) = (0..len - 1)
+            .map(|k| (k + len / 2 - 1) % (len - 1))
+            .find(|&i| chars[i] != chars[i + 1])
+    {
+        let mut swapped = chars.clone();
+        swapped.swap(i, i + 1);
+        typos.push((TypoKind::Transposition, swapped.into_iter().collect()));
+    }
+    if len >= 3 {
+        let mut shortened = chars.clone();
+        shortened.remove(len / 2);
+        typos.push((TypoKind::MissingLetter, shortened.into_iter().collect()));
+    }
+    if name.contains('-') {
+        typos.push((TypoKind::WrongSeparator, name.replace('-', "_")));
+    } else if name.contains('_') {
+        typos.push((TypoKind::WrongSeparator, name.replace('_', "-")));
+    }
+
+    typos.retain(|(_, typo)| typo != name);
+    typos
+}
+
+/// The lines of an output that make a suggestion: each line with a
+/// suggestion phrase, and the indented lines right after it, where Cobra lists
+/// its "Did you mean this?" candidates
+fn suggestions(output: &str) -> Vec<&str> {
+    let mut lines = Vec::new();
+    let mut in_suggestion = false;
+    for line in output.lines() {
+        if SUGGESTION_HINTS.iter().any(|hint| line.contains(hint)) {
+            in_suggestion = true;
+        } else if !(in_suggestion
+            && line.starts_with(char::is_whitespace)
+            && !line.trim().is_empty())
+        {
+            in_suggestion = false;
+        }
+        if in_suggestion {
+            lines.push(line);
+        }
+    }
+    lines
+}
+
+fn outcome(help_output: &Value, target: &str) -> TypoOutcome {
+    let output = format!(
+        "{}\n{}",
+        help_output["stdout"].as_str().unwrap_or_default(),
+        help_output["stderr"].as_str().unwrap_or_default()
+    )
+    .to_lowercase();
+    let target = target.to_lowercase();
+    let suggestions = suggestions(&output);
+    if suggestions.is_empty() {
+        if help_output["status"].as_i64() == Some(0) {
+            TypoOutcome::Accepted
+        } else {
+            TypoOutcome::Unhelpful
+        }
+    } else if suggestions.iter().any(|line| {
+        line.split(|c: char| !(c.is_alphanumeric() || c == '-' || c == '_'))
+            .any(|word| word == target)
+    }) {
+        TypoOutcome::Corrected
+    } else {
+        TypoOutcome::Misdirected
+    }
+}
+
+/// The subcommand names and aliases of a command, which typos must not hit
+fn subcommand_names(node: &Value) -> Vec<String> {
+    let mut names = Vec::new();
+    for (name, command) in node["children"]["COMMAND"]
+        .as_object()
+        .into_iter()
+        .flatten()
+    {
+        names.push(name.clone());
+        names.extend(
+            command["aliases"]
+                .as_array()
+                .into_iter()
+                .flatten()
+                .filter_map(|alias| alias.as_str().map(String::from)),
+        );
+    }
+    names
+}
+
+/// The command's own long flags. Inherited flags are probed where they are
+/// defined, and `--help` is already part of every probe.
+fn local_long_flags(node: &Value) -> Vec<String> {
+    node["children"]["FLAG"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter(|flag| flag["inherited"].as_bool() != Some(true))
+        .filter_map(|flag| flag["long"].as_str())
+        .filter_map(|long| long.strip_prefix("--").map(String::from))
+        .filter(|name| name != "help")
+        .collect()
+}
+
+fn probe_command(walked: &WalkedCommand, options: &CrawlOptions, report: &mut TypoProbeReport) {
+    let (node, command_path) = (walked.node, walked.command_path.as_str());
+    let mut coverage = TypoCoverage {
+        command_path: command_path.to_string(),
+        ..Default::default()
+    };
+
+    let subcommands = subcommand_names(node);
+    let flags = local_long_flags(node);
+    let mut probes = Vec::new();
+    for name in node["children"]["COMMAND"]
+        .as_object()
+        .into_iter()
+        .flatten()
+        .map(|(n, _)| n)
+    {
+        for (kind, typo) in derive_typos(name) {
+            if !subcommands.contains(&typo) {
+                probes.push((name.clone(), kind, typo));
+            }
+        }
+    }
+    for flag in &flags {
+        for (kind, typo) in derive_typos(flag) {
+            if !flags.contains(&typo) {
+                probes.push((format!("--{}", flag), kind, format!("--{}", typo)));
+            }
+        }
+    }
+
+    for (target, kind, typo) in probes {
+        // Asking for help keeps a typo that slips through from doing anything
+        // External plugins run through their own executable, as when crawled
+        let invocation = format!("{} {} --help", walked.command, typo);
+        let help_output = execute_full_command(&invocation, options);
+        let outcome = outcome(&help_output, &target);
+
+        let is_flag = target.starts_with("--");
+        let corrected = outcome == TypoOutcome::Corrected;
+        if is_flag {
+            coverage.flags_probed += 1;
+            coverage.flags_corrected += corrected as usize;
+        } else {
+            coverage.subcommands_probed += 1;
+            coverage.subcommands_corrected += corrected as usize;
+        }
+        report.results.push(TypoProbeResult {
+            command_path: command_path.to_string(),
+            target,
+            kind,
+            typo,
+            invocation,
+            status: help_output["status"].as_i64().unwrap_or(-1) as i32,
+            outcome,
+        });
+    }
+    if coverage.subcommands_probed + coverage.flags_probed > 0 {
+        report.coverage.push(coverage);
+    }
+}
+
+/// Tries typos of every subcommand and long flag of a parse, and records
+/// whether the program suggests the right correction
+pub fn probe_typos(structure: &Value, options: &CrawlOptions) -> TypoProbeReport {
+    let mut report = TypoProbeReport {
+        program: structure["name"].as_str().unwrap_or_default().to_string(),
+        version: structure["version"]
+            .as_str()
+            .unwrap_or_default()
+            .to_string(),
+        ..Default::default()
+    };
+    for walked in walk_commands(structure) {
+        probe_command(&walked, options, &mut report);
+    }
+    report
+}
@@ -1,138 +1,668 @@
 use regex::Regex;
+use std::collections::HashMap;
 
-use crate::models::{ComponentType, UsageComponent};
+use crate::argument_parser::is_argument_keyword;
+use crate::models::{ComponentType, ParsedUsage, Span, UsageComponent, UsageParseError};
 
-pub fn parse_usage_line(child_line: &str, command_name: &str) -> Vec<UsageComponent> {
+/// Words that stand for every flag of a command when put in brackets, e.g. `[flags]`
+const OPTIONS_SHORTCUTS: [&str; 4] = ["options", "flags", "option", "flag"];
+/// Longest single-dash word read as stacked short flags when its letters aren't
+/// all known flags, e.g. `-xvf`. Longer ones are Go-style long flags (`-version`).
+const MAX_UNKNOWN_STACK_LEN: usize = 3;
+
+/// Flags a command lists in its help page, by name, and whether each takes a
+/// value. Tells stacked short flags from single-dash long ones, and a flag's
+/// value from a positional argument following it.
+#[derive(Debug, Default)]
+pub struct KnownFlags {
+    takes_value: HashMap<String, bool>,
+}
+
+impl KnownFlags {
+    pub fn add(&mut self, name: &str, takes_value: bool) {
+        self.takes_value.insert(name.to_string(), takes_value);
+    }
+
+    fn contains(&self, name: &str) -> bool {
+        self.takes_value.contains_key(name)
+    }
+
+    fn takes_value(&self, name: &str) -> Option<bool> {
+        self.takes_value.get(name).copied()
+    }
+}
+
+pub fn parse_usage_line(
+    child_line: &str,
+    command_name: &str,
+    known_flags: &KnownFlags,
+) -> ParsedUsage {
+    let mut offset = child_line.len() - child_line.trim_start().len();
     let mut line = child_line.trim();
 
     // Remove "Usage:" and command path prefix
     if let Some(idx) = line.find(command_name) {
+        offset += idx + command_name.len();
         line = &line[idx + command_name.len()..];
     }
 
-    let line = line.trim();
-    if line.starts_with('-') {
-        return vec![];
+    let mut parser = Parser {
+        tokens: tokenize(line, offset),
+        position: 0,
+        errors: Vec::new(),
+        known_flags,
+        after_separator: false,
+    };
+    let components = parser.parse_line();
+    ParsedUsage {
+        components,
+        errors: parser.errors,
     }
-    parse_tokens(line)
 }
 
-fn parse_tokens(line: &str) -> Vec<UsageComponent> {
-    let key_value_re = Regex::new(r"^<[^>]+>=<[^>]+>$").unwrap();
-    let mut components = Vec::new();
-    let mut chars = line.chars().peekable();
-
-    while let Some(c) = chars.peek() {
-        match c {
-            '[' => {
-                chars.next(); // consume '['
-                let group_str = extract_until_matching(&mut chars, '[', ']');
-                let children = parse_tokens(&group_str);
-                components.push(UsageComponent {
-                    component_type: ComponentType::Group,
-                    name: String::new(),
-                    required: false,
-                    repeatable: group_str.ends_with("..."),
-                    key_value: false,
-                    alternatives: vec![],
-                    children,
-                });
-            }
-            '(' => {
-                chars.next(); // consume '('
-                let group_str = extract_until_matching(&mut chars, '(', ')');
-                let children = parse_alternatives(&group_str);
-                if children.is_empty() {
-                    continue;
-                }
-                components.push(UsageComponent {
-                    component_type: ComponentType::AlternativeGroup,
-                    name: String::new(),
-                    required: true,
-                    repeatable: group_str.ends_with("..."),
-                    key_value: false,
-                    alternatives: children,
-                    children: vec![],
-                });
-            }
-            _ => {
-                let token = extract_token(&mut chars);
-                if token.is_empty() {
-                    continue;
-                }
+#[derive(Debug, Clone, PartialEq)]
+enum TokenKind {
+    OpenBracket,
+    CloseBracket,
+    OpenParen,
+    CloseParen,
+    Pipe,
+    Ellipsis,
+    Word(String),
+}
 
-                let repeatable = token.ends_with("...");
-                let token_clean = if repeatable {
-                    token.trim_end_matches("...").trim().to_string()
-                } else {
-                    token.clone()
-                };
+#[derive(Debug, Clone)]
+struct Token {
+    kind: TokenKind,
+    span: Span,
+}
 
-                let key_value_re = &key_value_re;
-                let (name, key_value) = if key_value_re.is_match(&token_clean) {
-                    (token_clean, true)
-                } else {
-                    (token_clean, token.contains('='))
-                };
+/// Splits a usage line into brackets, pipes, ellipses and words. A `<placeholder>`
+/// is kept whole even when it contains spaces, and a trailing `...` is split off
+/// its word so that repetition is handled the same way for words and groups.
+fn tokenize(line: &str, offset: usize) -> Vec<Token> {
+    let mut tokens = Vec::new();
+    let mut chars = line.char_indices().peekable();
 
-                let component_type = if name.starts_with("--") {
-                    ComponentType::Flag
-                } else if name.starts_with("<") && name.ends_with(">") || key_value {
-                    ComponentType::Argument
-                } else {
-                    ComponentType::Keyword
-                };
+    while let Some(&(start, c)) = chars.peek() {
+        let kind = match c {
+            c if c.is_whitespace() => {
+                chars.next();
+                continue;
+            }
+            '[' => Some(TokenKind::OpenBracket),
+            ']' => Some(TokenKind::CloseBracket),
+            '(' => Some(TokenKind::OpenParen),
+            ')' => Some(TokenKind::CloseParen),
+            '|' => Some(TokenKind::Pipe),
+            _ => None,
+        };
+        if let Some(kind) = kind {
+            chars.next();
+            tokens.push(Token {
+                kind,
+                span: Span {
+                    start: offset + start,
+                    end: offset + start + 1,
+                },
+            });
+            continue;
+        }
 
-                components.push(UsageComponent {
-                    component_type,
-                    name,
-                    required: true,
-                    repeatable,
-                    key_value,
-                    alternatives: vec![],
-                    children: vec![],
-                });
+        let mut end = start;
+        let mut in_placeholder = false;
+        while let Some(&(idx, c)) = chars.peek() {
+            if !in_placeholder && (c.is_whitespace() || "[]()|".contains(c)) {
+                break;
+            }
+            if c == '<' {
+                in_placeholder = true;
+            } else if c == '>' {
+                in_placeholder = false;
             }
+            end = idx + c.len_utf8();
+            chars.next();
+        }
+
+        let word = &line[start..end];
+        let ellipsis = ["...", "…"].into_iter().find(|e| word.ends_with(e));
+        let word_end = ellipsis.map_or(end, |e| end - e.len());
+        if word_end > start {
+            tokens.push(Token {
+                kind: TokenKind::Word(line[start..word_end].to_string()),
+                span: Span {
+                    start: offset + start,
+                    end: offset + word_end,
+                },
+            });
+        }
+        if word_end < end {
+            tokens.push(Token {
+                kind: TokenKind::Ellipsis,
+                span: Span {
+                    start: offset + word_end,
+                    end: offset + end,
+                },
+            });
         }
     }
 
-    components
+    tokens
+}
+
+fn new_component(component_type: ComponentType, name: &str, span: Span) -> UsageComponent {
+    UsageComponent {
+        component_type,
+        name: name.to_string(),
+        required: true,
+        repeatable: false,
+        key_value: false,
+        alternatives: vec![],
+        children: vec![],
+        value: None,
+        span,
+    }
+}
+
+/// `<file>` or an upper-case placeholder such as `FILE`
+fn is_value_placeholder(word: &str) -> bool {
+    (word.starts_with('<') && word.ends_with('>')) || is_argument_keyword(word)
 }
 
-fn extract_token<I: Iterator<Item = char>>(chars: &mut std::iter::Peekable<I>) -> String {
-    chars
-        .by_ref()
-        .take_while(|&c| !matches!(c, ' ' | '[' | ']' | '(' | ')' | '|'))
-        .collect::<String>()
-        .trim_end()
-        .to_string()
+/// Recursive-descent parser over the grammar
+///
+/// ```text
+/// line        := expression
+/// expression  := sequence ('|' sequence)*
+/// sequence    := (element '...'?)*
+/// element     := '[' expression ']' | '(' expression ')' | word
+/// ```
+///
+/// Unmatched brackets and stray tokens are recorded as errors and skipped, so a
+/// malformed line still yields every component that could be recognized.
+struct Parser<'a> {
+    tokens: Vec<Token>,
+    position: usize,
+    errors: Vec<UsageParseError>,
+    known_flags: &'a KnownFlags,
+    /// Whether a `--` was passed, after which every word is an argument
+    after_separator: bool,
 }
 
-fn extract_until_matching<I: Iterator<Item = char>>(
-    chars: &mut std::iter::Peekable<I>,
-    open: char,
-    close: char,
-) -> String {
-    let mut content = String::new();
-    let mut depth = 1;
-
-    for c in chars.by_ref() {
-        if c == open {
-            depth += 1;
-        } else if c == close {
-            depth -= 1;
-            if depth == 0 {
-                break;
+impl Parser<'_> {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.position).cloned();
+        self.position += 1;
+        token
+    }
+
+    fn error(&mut self, message: &str, span: Span) {
+        self.errors.push(UsageParseError {
+            message: message.to_string(),
+            span,
+        });
+    }
+
+    fn parse_line(&mut self) -> Vec<UsageComponent> {
+        let mut components = Vec::new();
+        while self.peek().is_some() {
+            let start = self.position;
+            let alternatives = self.parse_expression(None);
+            components.extend(self.build_group(alternatives, true, None));
+            if self.position == start {
+                // A closing bracket without its opening one
+                let token = self.next().unwrap();
+                self.error("unmatched closing bracket", token.span);
+            }
+        }
+        components
+    }
+
+    /// Parses alternatives up to the `closing` token, which is left unconsumed
+    fn parse_expression(&mut self, closing: Option<&TokenKind>) -> Vec<Vec<UsageComponent>> {
+        let mut alternatives = vec![self.parse_sequence(closing)];
+        while let Some(token) = self.peek()
+            && token.kind == TokenKind::Pipe
+        {
+            let pipe = self.next().unwrap();
+            let sequence = self.parse_sequence(closing);
+            if sequence.is_empty() {
+                self.error("empty alternative", pipe.span);
+            } else {
+                alternatives.push(sequence);
             }
         }
-        content.push(c);
+        alternatives.retain(|sequence| !sequence.is_empty());
+        alternatives
+    }
+
+    fn parse_sequence(&mut self, closing: Option<&TokenKind>) -> Vec<UsageComponent> {
+        let mut sequence: Vec<UsageComponent> = Vec::new();
+
+        while let Some(token) = self.peek().cloned() {
+            match &token.kind {
+                TokenKind::Pipe => break,
+                TokenKind::CloseBracket | TokenKind::CloseParen => {
+                    if closing.is_some_and(|kind| *kind == token.kind) || closing.is_none() {
+                        break;
+                    }
+                    self.next();
+                    self.error("unmatched closing bracket", token.span);
+                }
+                TokenKind::Ellipsis => {
+                    self.next();
+                    match sequence.last_mut() {
+                        Some(previous) => {
+                            previous.repeatable = true;
+                            previous.span.end = token.span.end;
+                        }
+                        None => self.error("'...' without a preceding element", token.span),
+                    }
+                }
+                TokenKind::OpenBracket | TokenKind::OpenParen => {
+                    self.next();
+                    let (closing_kind, required) = if token.kind == TokenKind::OpenBracket {
+                        (TokenKind::CloseBracket, false)
+                    } else {
+                        (TokenKind::CloseParen, true)
+                    };
+                    let alternatives = self.parse_expression(Some(&closing_kind));
+                    let end = match self.peek() {
+                        Some(close) if close.kind == closing_kind => self.next().unwrap().span.end,
+                        _ => {
+                            self.error("unclosed bracket", token.span);
+                            self.tokens
+                                .get(self.position.saturating_sub(1))
+                                .map_or(token.span.end, |last| last.span.end)
+                        }
+                    };
+                    let span = Span {
+                        start: token.span.start,
+                        end,
+                    };
+                    if alternatives.is_empty() {
+                        self.error("empty group", span);
+                        continue;
+                    }
+                    sequence.extend(self.build_group(alternatives, required, Some(span)));
+                }
+                TokenKind::Word(word) => {
+                    self.next();
+                    let components = self.parse_word(word, token.span);
+                    sequence.extend(components);
+                }
+            }
+        }
+
+        sequence
+    }
+
+    /// Turns parsed alternatives into components. A bracketed or parenthesized
+    /// group becomes a single component; at the top level (`span` is `None`) a
+    /// lone sequence is returned as is.
+    fn build_group(
+        &self,
+        mut alternatives: Vec<Vec<UsageComponent>>,
+        required: bool,
+        span: Option<Span>,
+    ) -> Vec<UsageComponent> {
+        let span_of = |sequence: &[UsageComponent]| Span {
+            start: sequence.first().map_or(0, |c| c.span.start),
+            end: sequence.last().map_or(0, |c| c.span.end),
+        };
+
+        if alternatives.len() > 1 {
+            let span = span.unwrap_or_else(|| Span {
+                start: span_of(&alternatives[0]).start,
+                end: span_of(alternatives.last().unwrap()).end,
+            });
+            let mut group = new_component(ComponentType::AlternativeGroup, "", span);
+            group.required = required;
+            group.alternatives = alternatives
+                .into_iter()
+                .map(|mut sequence| {
+                    if sequence.len() == 1 {
+                        sequence.pop().unwrap()
+                    } else {
+                        let mut branch =
+                            new_component(ComponentType::Group, "", span_of(&sequence));
+                        branch.children = sequence;
+                        branch
+                    }
+                })
+                .collect();
+            return vec![group];
+        }
+
+        let sequence = alternatives.pop().unwrap_or_default();
+        let Some(span) = span else {
+            return sequence;
+        };
+
+        // `[options]`, `[flags]`, `[global options]`
+        let words: Vec<&str> = sequence
+            .iter()
+            .filter(|c| c.component_type == ComponentType::Keyword)
+            .map(|c| c.name.as_str())
+            .collect();
+        if !required
+            && words.len() == sequence.len()
+            && words.len() <= 2
+            && words
+                .last()
+                .is_some_and(|w| OPTIONS_SHORTCUTS.contains(&w.to_lowercase().as_str()))
+        {
+            let mut shortcut =
+                new_component(ComponentType::OptionsShortcut, &words.join(" "), span);
+            shortcut.required = false;
+            return vec![shortcut];
+        }
+
+        let mut group = new_component(ComponentType::Group, "", span);
+        group.required = required;
+        group.children = sequence;
+        vec![group]
+    }
+
+    fn parse_word(&mut self, word: &str, span: Span) -> Vec<UsageComponent> {
+        if self.after_separator {
+            return vec![new_component(ComponentType::Argument, word, span)];
+        }
+        if word == "--" {
+            self.after_separator = true;
+            return vec![new_component(ComponentType::Separator, word, span)];
+        }
+
+        if word.starts_with('-') && word.len() > 1 {
+            let (name, value) = match word.split_once('=') {
+                Some((name, value)) => (name, Some(value.to_string()).filter(|v| !v.is_empty())),
+                None => (word, None),
+            };
+
+            // `-abc` stacks the short flags `-a`, `-b` and `-c`, unless it's a
+            // single-dash long flag such as `-name`
+            let letters = &name[1..];
+            if !name.starts_with("--")
+                && value.is_none()
+                && letters.len() > 1
+                && letters.chars().all(|c| c.is_ascii_alphanumeric())
+                && !self.known_flags.contains(name)
+                && (letters.len() <= MAX_UNKNOWN_STACK_LEN
+                    || letters
+                        .chars()
+                        .all(|c| self.known_flags.contains(&format!("-{}", c))))
+            {
+                return letters
+                    .char_indices()
+                    .map(|(idx, letter)| {
+                        let start = span.start + 1 + idx;
+                        new_component(
+                            ComponentType::Flag,
+                            &format!("-{}", letter),
+                            Span {
+                                start,
+                                end: start + 1,
+                            },
+                        )
+                    })
+                    .collect();
+            }
+
+            let mut flag = new_component(ComponentType::Flag, name, span);
+            flag.value = value;
+            // `--output <file>` binds the placeholder that follows to the flag.
+            // Unless the flag is known to take a value, a bare placeholder after
+            // a short flag is a positional argument, as in `-v FILE`.
+            if flag.value.is_none()
+                && let Some(Token {
+                    kind: TokenKind::Word(next_word),
+                    span: next_span,
+                }) = self.peek().cloned()
+                && match self.known_flags.takes_value(name) {
+                    Some(takes_value) => takes_value && !next_word.starts_with('-'),
+                    None => {
+                        is_value_placeholder(&next_word)
+                            && (name.starts_with("--") || next_word.starts_with('<'))
+                    }
+                }
+            {
+                self.next();
+                flag.value = Some(next_word);
+                flag.span.end = next_span.end;
+            }
+            return vec![flag];
+        }
+
+        let key_value_re = Regex::new(r"^<[^>]+>=<[^>]+>$").unwrap();
+        let key_value = key_value_re.is_match(word) || word.contains('=');
+        let component_type = if key_value || is_value_placeholder(word) {
+            ComponentType::Argument
+        } else {
+            ComponentType::Keyword
+        };
+        let mut component = new_component(component_type, word, span);
+        component.key_value = key_value;
+        vec![component]
     }
-    content.trim().to_string()
 }
 
-fn parse_alternatives(group: &str) -> Vec<UsageComponent> {
-    group
-        .split('|')
-        .flat_map(|part| parse_tokens(part.trim()))
-        .collect()
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Renders components back into usage notation, tagging each element with
+    /// its type so the table below reads like the usage lines themselves
+    fn render(components: &[UsageComponent]) -> String {
+        components
+            .iter()
+            .map(|component| {
+                let rendered = match component.component_type {
+                    ComponentType::Flag => match &component.value {
+                        Some(value) => format!("flag:{}={}", component.name, value),
+                        None => format!("flag:{}", component.name),
+                    },
+                    ComponentType::Argument | ComponentType::KeyValuePair => {
+                        format!("arg:{}", component.name)
+                    }
+                    ComponentType::Keyword => component.name.clone(),
+                    ComponentType::OptionsShortcut => format!("options:{}", component.name),
+                    ComponentType::Separator => component.name.clone(),
+                    ComponentType::Group => {
+                        let children = render(&component.children);
+                        if component.required {
+                            format!("({})", children)
+                        } else {
+                            format!("[{}]", children)
+                        }
+                    }
+                    ComponentType::AlternativeGroup => {
+                        let alternatives = component
+                            .alternatives
+                            .iter()
+                            .map(|alternative| render(std::slice::from_ref(alternative)))
+                            .collect::<Vec<_>>()
+                            .join(" | ");
+                        if component.required {
+                            format!("({})", alternatives)
+                        } else {
+                            format!("[{}]", alternatives)
+                        }
+                    }
+                };
+                if component.repeatable {
+                    format!("{}...", rendered)
+                } else {
+                    rendered
+                }
+            })
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    fn parse(line: &str, command_name: &str, known_flags: &KnownFlags) -> String {
+        let parsed = parse_usage_line(line, command_name, known_flags);
+        assert!(parsed.errors.is_empty(), "{}: {:?}", line, parsed.errors);
+        render(&parsed.components)
+    }
+
+    #[test]
+    fn parses_real_world_usage_lines() {
+        let corpus = [
+            (
+                "git [--version] [--help] [-C <path>] [-c <name>=<value>] <command> [<args>]",
+                "git",
+                "[flag:--version] [flag:--help] [flag:-C=<path>] [flag:-c=<name>=<value>] arg:<command> [arg:<args>]",
+            ),
+            (
+                "git add [--verbose | -v] [--dry-run | -n] [--] [<pathspec>...]",
+                "add",
+                "[flag:--verbose | flag:-v] [flag:--dry-run | flag:-n] [--] [arg:<pathspec>...]",
+            ),
+            (
+                "git commit [-a | --interactive | --patch] [-s] [-m <msg>] [--] [<pathspec>...]",
+                "commit",
+                "[flag:-a | flag:--interactive | flag:--patch] [flag:-s] [flag:-m=<msg>] [--] [arg:<pathspec>...]",
+            ),
+            (
+                "docker run [OPTIONS] IMAGE [COMMAND] [ARG...]",
+                "run",
+                "options:OPTIONS arg:IMAGE [COMMAND] [arg:ARG...]",
+            ),
+            (
+                "docker network connect [OPTIONS] NETWORK CONTAINER",
+                "connect",
+                "options:OPTIONS arg:NETWORK arg:CONTAINER",
+            ),
+            (
+                "kubectl logs [-f] [-p] (POD | TYPE/NAME) [flags]",
+                "logs",
+                "[flag:-f] [flag:-p] (arg:POD | arg:TYPE/NAME) options:flags",
+            ),
+            (
+                "kubectl exec (POD | TYPE/NAME) [-c CONTAINER] [flags] -- COMMAND [args...]",
+                "exec",
+                "(arg:POD | arg:TYPE/NAME) [flag:-c arg:CONTAINER] options:flags -- arg:COMMAND [arg:args...]",
+            ),
+            (
+                "terraform [global options] <subcommand> [args]",
+                "terraform",
+                "options:global options arg:<subcommand> [args]",
+            ),
+            (
+                "terraform [-version] [-help] <command> [args]",
+                "terraform",
+                "[flag:-version] [flag:-help] arg:<command> [args]",
+            ),
+            (
+                "go vet [-n] [-x] [-vettool prog] [build flags] [packages]",
+                "vet",
+                "[flag:-n] [flag:-x] [flag:-vettool prog] options:build flags [packages]",
+            ),
+            (
+                "ls [OPTION]... [FILE]...",
+                "ls",
+                "options:OPTION... [arg:FILE]...",
+            ),
+            (
+                "cp [OPTION]... [-T] SOURCE DEST",
+                "cp",
+                "options:OPTION... [flag:-T] arg:SOURCE arg:DEST",
+            ),
+            (
+                "grep [OPTION]... PATTERNS [FILE]...",
+                "grep",
+                "options:OPTION... arg:PATTERNS [arg:FILE]...",
+            ),
+            ("rm -rf FILE", "rm", "flag:-r flag:-f arg:FILE"),
+            (
+                "tar -xvf ARCHIVE",
+                "tar",
+                "flag:-x flag:-v flag:-f arg:ARCHIVE",
+            ),
+            ("cat -v FILE", "cat", "flag:-v arg:FILE"),
+        ];
+
+        for (line, command_name, expected) in corpus {
+            assert_eq!(
+                parse(line, command_name, &KnownFlags::default()),
+                expected,
+                "{}",
+                line
+            );
+        }
+    }
+
+    #[test]
+    fn reads_flags_with_the_help_page_flags() {
+        let mut known_flags = KnownFlags::default();
+        known_flags.add("-c", true);
+        known_flags.add("-var", true);
+        known_flags.add("-auto-approve", false);
+        known_flags.add("-v", false);
+
+        let corpus = [
+            (
+                "kubectl exec (POD | TYPE/NAME) [-c CONTAINER] [flags] -- COMMAND [args...]",
+                "exec",
+                "(arg:POD | arg:TYPE/NAME) [flag:-c=CONTAINER] options:flags -- arg:COMMAND [arg:args...]",
+            ),
+            (
+                "terraform apply [-auto-approve] [-var NAME=VALUE] [PLAN]",
+                "apply",
+                "[flag:-auto-approve] [flag:-var=NAME=VALUE] [arg:PLAN]",
+            ),
+            ("cat -v FILE", "cat", "flag:-v arg:FILE"),
+        ];
+
+        for (line, command_name, expected) in corpus {
+            assert_eq!(
+                parse(line, command_name, &known_flags),
+                expected,
+                "{}",
+                line
+            );
+        }
+    }
+
+    #[test]
+    fn derives_arguments_from_alternatives_and_after_the_separator() {
+        let mut known_flags = KnownFlags::default();
+        known_flags.add("-c", true);
+        let parsed = parse_usage_line(
+            "kubectl exec (POD | TYPE/NAME) [-c CONTAINER] [flags] -- COMMAND [args...]",
+            "exec",
+            &known_flags,
+        );
+        let arguments: Vec<(String, bool, bool)> =
+            crate::argument_parser::derive_arguments(&[parsed.components], &[])
+                .into_iter()
+                .map(|argument| (argument.name, argument.required, argument.variadic))
+                .collect();
+        assert_eq!(
+            arguments,
+            [
+                ("POD".to_string(), false, false),
+                ("TYPE/NAME".to_string(), false, false),
+                ("COMMAND".to_string(), true, false),
+                ("args".to_string(), false, true),
+            ]
+        );
+    }
+
+    #[test]
+    fn records_errors_and_recovers() {
+        let parsed = parse_usage_line(
+            "git log [--oneline <since>..<until>",
+            "log",
+            &KnownFlags::default(),
+        );
+        assert_eq!(parsed.errors.len(), 1);
+        assert_eq!(parsed.errors[0].message, "unclosed bracket");
+        assert_eq!(parsed.errors[0].span, Span { start: 8, end: 9 });
+        assert!(!parsed.components.is_empty());
+    }
 }
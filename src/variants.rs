@@ -0,0 +1,200 @@
+use serde_json::{Value, json};
+
+use crate::cli_parser::flag_signature;
+use crate::models::Variant;
+
+/// Parses `NAME` or `NAME:KEY=VALUE,KEY=VALUE`
+pub fn parse_variant(spec: &str) -> Result<Variant, String> {
+    let (name, env_spec) = spec.split_once(':').unwrap_or((spec, ""));
+    if name.is_empty() {
+        return Err(format!(
+            "Invalid variant '{}', expected NAME[:KEY=VALUE,...]",
+            spec
+        ));
+    }
+
+    let mut env = Vec::new();
+    for pair in env_spec.split(',').filter(|pair| !pair.is_empty()) {
+        match pair.split_once('=') {
+            Some((key, value)) if !key.is_empty() => {
+                env.push((key.to_string(), value.to_string()));
+            }
+            _ => {
+                return Err(format!(
+                    "Invalid variable '{}' in variant '{}', expected KEY=VALUE",
+                    pair, name
+                ));
+            }
+        }
+    }
+
+    Ok(Variant {
+        name: name.to_string(),
+        env,
+    })
+}
+
+fn add_variant(node: &mut Value, variant_name: &str) {
+    if !node["variants"].is_array() {
+        node["variants"] = json!([]);
+    }
+    let variants = node["variants"].as_array_mut().unwrap();
+    if !variants.iter().any(|v| v == variant_name) {
+        variants.push(json!(variant_name));
+    }
+}
+
+fn flags_mut(node: &mut Value) -> Option<&mut Vec<Value>> {
+    node.get_mut("children")?.get_mut("FLAG")?.as_array_mut()
+}
+
+fn commands_mut(node: &mut Value) -> Option<&mut serde_json::Map<String, Value>> {
+    node.get_mut("children")?
+        .get_mut("COMMAND")?
+        .as_object_mut()
+}
+
+/// Marks the flags and subcommands of a node, and everything below them, as
+/// found in a variant
+fn add_variant_below(node: &mut Value, variant_name: &str) {
+    if let Some(flags) = flags_mut(node) {
+        for flag in flags {
+            add_variant(flag, variant_name);
+        }
+    }
+    if let Some(commands) = commands_mut(node) {
+        for command in commands.values_mut() {
+            add_variant(command, variant_name);
+            add_variant_below(command, variant_name);
+        }
+    }
+}
+
+fn merge_node(merged: &mut Value, node: &Value, variant_name: &str) {
+    for flag in node["children"]["FLAG"].as_array().into_iter().flatten() {
+        let Some(merged_flags) = flags_mut(merged) else {
+            break;
+        };
+        let signature = flag_signature(flag);
+        match merged_flags
+            .iter_mut()
+            .find(|merged_flag| flag_signature(merged_flag) == signature)
+        {
+            Some(merged_flag) => add_variant(merged_flag, variant_name),
+            None => {
+                let mut flag = flag.clone();
+                add_variant(&mut flag, variant_name);
+                merged_flags.push(flag);
+            }
+        }
+    }
+
+    for (name, command) in node["children"]["COMMAND"]
+        .as_object()
+        .into_iter()
+        .flatten()
+    {
+        let Some(merged_commands) = commands_mut(merged) else {
+            break;
+        };
+        match merged_commands.get_mut(name) {
+            Some(merged_command) => {
+                add_variant(merged_command, variant_name);
+                merge_node(merged_command, command, variant_name);
+            }
+            None => {
+                let mut command = command.clone();
+                add_variant(&mut command, variant_name);
+                add_variant_below(&mut command, variant_name);
+                merged_commands.insert(name.clone(), command);
+            }
+        }
+    }
+}
+
+/// Merges the crawls of several variants into their union. Every command and
+/// flag lists the variants it was found in under `variants`. Anything else
+/// (descriptions, usage lines, help pages, latencies) is taken from the first
+/// variant that has the command.
+pub fn merge_variants(crawls: Vec<(Variant, Value)>) -> Value {
+    let mut crawls = crawls.into_iter();
+    let Some((first_variant, mut merged)) = crawls.next() else {
+        return json!({});
+    };
+    add_variant_below(&mut merged, &first_variant.name);
+    let mut variants = vec![first_variant];
+    let mut report_nodes = take_report_nodes(&mut merged, &variants[0].name);
+
+    for (variant, mut structure) in crawls {
+        report_nodes.extend(take_report_nodes(&mut structure, &variant.name));
+        merge_latency(&mut merged, &structure);
+        merge_node(&mut merged, &structure, &variant.name);
+        variants.push(variant);
+    }
+
+    merged["metadata"]["variants"] = json!(
+        variants
+            .iter()
+            .map(|variant| {
+                let env: serde_json::Map<String, Value> = variant
+                    .env
+                    .iter()
+                    .map(|(key, value)| (key.clone(), json!(value)))
+                    .collect();
+                json!({ "name": variant.name, "env": env })
+            })
+            .collect::<Vec<_>>()
+    );
+    merged["metadata"]["crawl_report"] = json!({ "nodes": report_nodes });
+    merged
+}
+
+/// Adds the latency of the commands the merged structure hasn't profiled yet
+fn merge_latency(merged: &mut Value, structure: &Value) {
+    let Some(commands) = structure["metadata"]["latency"]["commands"].as_array() else {
+        return;
+    };
+    let Some(merged_commands) = merged["metadata"]["latency"]["commands"].as_array_mut() else {
+        return;
+    };
+    for command in commands {
+        if !merged_commands
+            .iter()
+            .any(|merged_command| merged_command["command_path"] == command["command_path"])
+        {
+            merged_commands.push(command.clone());
+        }
+    }
+}
+
+/// The crawl report entries of one variant's crawl, labelled with the variant
+fn take_report_nodes(structure: &mut Value, variant_name: &str) -> Vec<Value> {
+    let mut nodes = match structure["metadata"]["crawl_report"]["nodes"].take() {
+        Value::Array(nodes) => nodes,
+        _ => Vec::new(),
+    };
+    for node in &mut nodes {
+        node["variant"] = json!(variant_name);
+    }
+    nodes
+}
+
+/// Keeps only the commands and flags found in a variant. Structures parsed
+/// without variants are left as they are.
+pub fn filter_by_variant(node: &mut Value, variant_name: &str) {
+    let in_variant = |entry: &Value| {
+        entry
+            .get("variants")
+            .and_then(Value::as_array)
+            .is_none_or(|variants| variants.iter().any(|v| v == variant_name))
+    };
+    if let Some(flags) = flags_mut(node) {
+        flags.retain(in_variant);
+    }
+    if let Some(commands) = commands_mut(node) {
+        commands.retain(|_, command| in_variant(command));
+        for command in commands.values_mut() {
+            filter_by_variant(command, variant_name);
+        }
+    }
+}
@@ -61,7 +61,11 @@ class CliCommandCard extends HTMLElement {
           ${!parent ? `<div><h4 class="label">Version:</h4><div class="field-value">${version}</div></div>` : ''}
           <div class="slot-container">
           <slot name="outputs"></slot>
+          <slot name="aliases"></slot>
+          <slot name="arguments"></slot>
           <slot name="flags"></slot>
+          <slot name="examples"></slot>
+          <slot name="help-topics"></slot>
           <slot name="usage"></slot>
           <slot name="other"></slot>
           </div>
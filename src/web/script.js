@@ -15,12 +15,43 @@ document.addEventListener('DOMContentLoaded', async () => {
   renderCommands(container, cliData);
 })
 
+function flagScope(flag) {
+  if (flag.inherited) {
+    return flag.defined_in ? `inherited from ${flag.defined_in}` : 'inherited';
+  }
+  return flag.persistent ? 'persistent' : 'local';
+}
+
+function flagDetails(flag) {
+  const details = [];
+  if (flag.choices?.length) details.push(`one of: ${flag.choices.join(', ')}`);
+  if (flag.env?.length) details.push(`env: ${flag.env.join(', ')}`);
+  if (flag.negates) details.push(`negates ${flag.negates}`);
+  if (flag.repeatable) details.push('repeatable');
+  if (flag.deprecated) details.push('deprecated');
+  return details.length ? `<br><small style="color: #aaa;">${details.join(' · ')}</small>` : '';
+}
+
+// Entries flagged by `clint serve --min-confidence` are dimmed, with their evidence on hover
+function lowConfidenceTitle(node) {
+  return `Low confidence (${node.confidence}): ${(node.evidence || []).join(', ') || 'no evidence'}`;
+}
+
+function rowAttributes(node) {
+  if (!node.low_confidence) return 'style="height: 1.5rem;"';
+  return `style="height: 1.5rem; opacity: .5;" title="${lowConfidenceTitle(node)}"`;
+}
+
 function createCard(command, parent = '') {
   const el = document.createElement('cli-command-card');
   el.setAttribute('name', command.name || '');
   el.setAttribute('description', command.description || '');
   el.setAttribute('parent', parent);
   el.setAttribute('version', command.version || '');
+  if (command.low_confidence) {
+    el.style.opacity = '.5';
+    el.title = lowConfidenceTitle(command);
+  }
 
   // Outputs slot
   if (command.outputs) {
@@ -53,13 +84,67 @@ ${rawText}
             <th scope="col" style="min-width: max-content; text-align: start; padding: 0 1rem;">Long</th>
             <th scope="col" style="min-width: max-content; text-align: start; padding: 0 1rem;">Data Type</th>
             <th scope="col" style="min-width: max-content; text-align: start; padding: 0 1rem;">Description</th>
+            <th scope="col" style="min-width: max-content; text-align: start; padding: 0 1rem;">Default</th>
+            <th scope="col" style="min-width: max-content; text-align: start; padding: 0 1rem;">Scope</th>
         </thead>
         <tbody>` +
-      command.children.FLAG.map(f => `<tr style="height: 1.5rem;"><td style="text-align: start; padding: 0 1rem">${f.short || ''}</td><td style="text-align: start; padding: 0 1rem">${f.long || ''}</td><td style="text-align: start; padding: 0 1rem">${f.data_type || 'boolean'}</td><td style="text-align: start; padding: 0 1rem">${f.description || ''}</td></tr>`).join('') +
+      command.children.FLAG.map(f => `<tr ${rowAttributes(f)}><td style="text-align: start; padding: 0 1rem">${f.short || ''}</td><td style="text-align: start; padding: 0 1rem">${f.long || ''}</td><td style="text-align: start; padding: 0 1rem">${f.data_type || f.value_placeholder || 'boolean'}</td><td style="text-align: start; padding: 0 1rem">${f.description || ''}${flagDetails(f)}</td><td style="text-align: start; padding: 0 1rem">${f.default ?? ''}</td><td style="text-align: start; padding: 0 1rem">${flagScope(f)}</td></tr>`).join('') +
       `</tbody></table>`;
     el.appendChild(flags);
   }
 
+  // Arguments slot
+  if (command.arguments?.length) {
+    const args = document.createElement('div');
+    args.setAttribute('slot', 'arguments');
+    args.innerHTML = `
+    <h4>Arguments</h4>
+      <table>
+        <thead style="background-color: #333; height: 2.5rem;">
+          <tr style="color: #f7f7f7;" >
+            <th scope="col" style="min-width: max-content; text-align: start; padding: 0 1rem;">Position</th>
+            <th scope="col" style="min-width: max-content; text-align: start; padding: 0 1rem;">Name</th>
+            <th scope="col" style="min-width: max-content; text-align: start; padding: 0 1rem;">Required</th>
+            <th scope="col" style="min-width: max-content; text-align: start; padding: 0 1rem;">Description</th>
+        </thead>
+        <tbody>` +
+      command.arguments.map(a => `<tr ${rowAttributes(a)}><td style="text-align: start; padding: 0 1rem">${a.position + 1}</td><td style="text-align: start; padding: 0 1rem"><code>${a.name}${a.variadic ? '...' : ''}</code></td><td style="text-align: start; padding: 0 1rem">${a.required ? 'yes' : 'no'}</td><td style="text-align: start; padding: 0 1rem">${a.description || ''}</td></tr>`).join('') +
+      `</tbody></table>`;
+    el.appendChild(args);
+  }
+
+  // Aliases slot
+  if (command.aliases?.length) {
+    const aliases = document.createElement('div');
+    aliases.setAttribute('slot', 'aliases');
+    aliases.innerHTML = `<h4>Aliases</h4><p>` +
+      command.aliases.map(a => `<code>${a}</code>`).join(', ') +
+      `</p>`;
+    el.appendChild(aliases);
+  }
+
+  // Examples slot
+  if (command.examples?.length) {
+    const examples = document.createElement('div');
+    examples.setAttribute('slot', 'examples');
+    examples.innerHTML = `<h4>Examples</h4>` +
+      command.examples.map(e => `
+        ${e.comment ? `<p style="margin: .5rem 0 .2rem; color: #ccc;">${e.comment}</p>` : ''}
+        <pre style="margin: 0; padding: .5rem 1rem; background-color: #333; color: #f7f7f7;"><code>${e.command_line}</code></pre>
+      `).join('');
+    el.appendChild(examples);
+  }
+
+  // Help topics slot
+  if (command.children?.HELP_TOPIC?.length) {
+    const topics = document.createElement('div');
+    topics.setAttribute('slot', 'help-topics');
+    topics.innerHTML = `<h4>Additional Help Topics</h4><ul>` +
+      command.children.HELP_TOPIC.map(t => `<li><code>${t.command_path}</code> ${t.description || ''}</li>`).join('') +
+      `</ul>`;
+    el.appendChild(topics);
+  }
+
   // Children slot - all children become nested cli-command-card elements
   if (command.children?.COMMAND && Object.keys(command.children.COMMAND).length > 0) {
     const childrenContainer = document.createElement('div');
@@ -96,6 +181,10 @@ function renderCommands(container, cliData) {
     const section = document.createElement('section');
     const header = document.createElement('h2');
     header.textContent = `${cliData.name} ${commandName}`;
+    if (commandData.low_confidence) {
+      header.style.opacity = '.5';
+      header.title = lowConfidenceTitle(commandData);
+    }
     section.appendChild(header);
     
     // Add description of the top-level command
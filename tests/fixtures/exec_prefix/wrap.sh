@@ -0,0 +1,7 @@
+#!/bin/sh
+# Stands in for a container or nix shell: runs its arguments with the
+# programs in bin/ on PATH, so they can only be found through the wrapper.
+#
+#   clint parse fakecli --exec-prefix 'tests/fixtures/exec_prefix/wrap.sh {argv}'
+dir=$(cd "$(dirname "$0")" && pwd)
+PATH="$dir/bin:$PATH" exec "$@"